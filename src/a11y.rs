@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Accessibility metadata that GTK can't work out by itself, such as the names of buttons that
+//! only show an icon, and telling screen readers about changes like the number of results of a
+//! search.
+
+use gtk4::{
+	glib::Object, prelude::*, Accessible, AccessibleProperty, AccessibleRelation, AccessibleRole,
+	Align, CheckButton, Label, Orientation,
+};
+
+/// Names a widget for assistive technology, such as a button that only shows an icon, or a
+/// row whose name is the text of one of its children.
+pub fn set_label(widget: &impl IsA<Accessible>, label: &str) {
+	widget.update_property(&[(AccessibleProperty::Label, &label)]);
+}
+
+/// Describes a widget for assistive technology, in addition to its name.
+pub fn set_description(widget: &impl IsA<Accessible>, description: &str) {
+	widget.update_property(&[(AccessibleProperty::Description, &description)]);
+}
+
+/// Tells assistive technology where a widget is among a set of related ones, such as an option
+/// of a radio group, counting from 0.
+pub fn set_position(widget: &impl IsA<Accessible>, index: usize, count: usize) {
+	widget.update_relation(&[
+		(AccessibleRelation::PosInSet, &(index as i32 + 1)),
+		(AccessibleRelation::SetSize, &(count as i32)),
+	]);
+}
+
+/// Creates a box that's announced as a group of related widgets with the given name, such as a
+/// settings group. Groups without a title are left unnamed.
+pub fn group(label: &str) -> gtk4::Box {
+	labeled_box(AccessibleRole::Group, label)
+}
+
+/// Creates a box for the options of a radio group, which is announced as one group with the
+/// given name.
+pub fn radio_group(label: &str) -> gtk4::Box {
+	labeled_box(AccessibleRole::RadioGroup, label)
+}
+
+/// Creates a box for the contents of a dialog with the given title, which keeps the rest of
+/// the window from being used while it's shown.
+pub fn dialog(title: &str) -> gtk4::Box {
+	let dialog = labeled_box(AccessibleRole::Dialog, title);
+	dialog.update_property(&[(AccessibleProperty::Modal, &true)]);
+	dialog
+}
+
+fn labeled_box(role: AccessibleRole, label: &str) -> gtk4::Box {
+	let group: gtk4::Box = Object::new(&[
+		("accessible-role", &role),
+		("orientation", &Orientation::Vertical),
+	])
+	.expect("Failed to create group");
+	if !label.is_empty() {
+		set_label(&group, label);
+	}
+	group
+}
+
+/// Creates a check button that's announced as an option of a radio group, rather than as a
+/// check box, once it's added to a group with [`CheckButton::set_group`].
+pub fn radio_button() -> CheckButton {
+	Object::new(&[
+		("accessible-role", &AccessibleRole::Radio),
+		("valign", &Align::Center),
+	])
+	.expect("Failed to create radio button")
+}
+
+/// Creates a label that screen readers read out when it's shown and whenever its text
+/// changes, such as the number of networks found by a scan.
+pub fn status_label() -> Label {
+	Object::new(&[
+		("accessible-role", &AccessibleRole::Status),
+		("halign", &Align::Start),
+		("wrap", &true),
+	])
+	.expect("Failed to create status label")
+}
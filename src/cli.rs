@@ -0,0 +1,543 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The headless command line interface, which works on the same settings keys as the GUI
+//! without needing a display.
+
+use crate::{
+	config::{Bundle, Config, Key, KeyKind, Profile, Profiles, Source, Value},
+	i18n::tr,
+	sections::{self, Control, Row, SectionLayout, SettingsGroup},
+};
+use anyhow::{bail, Context, Result};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use serde_json::json;
+use std::{
+	io::{self, BufRead, Write},
+	path::{Path, PathBuf},
+};
+
+const USAGE: &str = "\
+Usage:
+  cosmic-settings [PAGE] [--search QUERY]          Open the settings window
+  cosmic-settings list [SECTION] [--search QUERY]  List settings keys by section
+  cosmic-settings get KEY                          Print the value of a key
+  cosmic-settings set KEY VALUE                    Change the value of a key
+  cosmic-settings reset KEY...                     Restore keys to their defaults
+  cosmic-settings export FILE [SECTION...]         Save the settings of every section or
+                                                   the given ones to a bundle
+  cosmic-settings import FILE [--yes]              Show how a bundle differs from the
+                                                   current settings, and apply it
+  cosmic-settings profile list                     List profiles and the settings they change
+  cosmic-settings profile set NAME KEY=VALUE...    Create a profile or change its settings
+  cosmic-settings profile unset NAME KEY...        Remove settings from a profile
+  cosmic-settings profile delete NAME              Delete a profile
+  cosmic-settings profile activate NAME            Apply a profile over the current settings
+  cosmic-settings profile deactivate               Restore the settings from before the
+                                                   active profile was applied
+  cosmic-settings --dump-layout json               Print every page, group and setting
+  cosmic-settings help                             Show this message
+
+Pages are written as SECTION or SECTION/PAGE, such as `wifi` or `desktop/dock`.
+Keys are written as COMPONENT/NAME, as shown by `cosmic-settings list`.";
+
+/// Where the settings window should open, as given on the command line.
+#[derive(Debug, Default)]
+pub struct Launch {
+	/// The path of the page to show, such as `wifi` or `desktop/dock`.
+	pub page: Option<String>,
+	/// The text to search for.
+	pub search: Option<String>,
+}
+
+impl Launch {
+	/// Parses the arguments that follow the program name.
+	pub fn parse(args: &[String]) -> Result<Self> {
+		let mut launch = Self::default();
+		let mut args = args.iter();
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--search" => {
+					launch.search = Some(args.next().context("--search requires a query")?.clone())
+				}
+				_ if arg.starts_with('-') => bail!("unknown option '{}'\n\n{}", arg, USAGE),
+				_ if launch.page.is_none() => launch.page = Some(arg.clone()),
+				_ => bail!("unexpected argument '{}'\n\n{}", arg, USAGE),
+			}
+		}
+		Ok(launch)
+	}
+}
+
+/// A headless command given on the command line.
+pub enum Command {
+	List {
+		section: Option<String>,
+		search: Option<String>,
+	},
+	Get {
+		key: String,
+	},
+	Set {
+		key: String,
+		value: String,
+	},
+	Reset {
+		keys: Vec<String>,
+	},
+	Export {
+		path: PathBuf,
+		sections: Vec<String>,
+	},
+	Import {
+		path: PathBuf,
+		/// Whether to apply the changes without asking.
+		yes: bool,
+	},
+	Profile(ProfileCommand),
+	DumpLayout,
+	Help,
+}
+
+/// A command that manages settings profiles.
+pub enum ProfileCommand {
+	List,
+	Set {
+		name: String,
+		/// The values to set, as `KEY=VALUE`.
+		values: Vec<String>,
+	},
+	Unset {
+		name: String,
+		keys: Vec<String>,
+	},
+	Delete {
+		name: String,
+	},
+	Activate {
+		name: String,
+	},
+	Deactivate,
+}
+
+impl Command {
+	/// Parses the arguments that follow the program name.
+	///
+	/// Returns `None` if they don't name a headless command, in which case the GUI is started.
+	pub fn parse(args: &[String]) -> Result<Option<Self>> {
+		let (command, args) = match args.split_first() {
+			Some((command, args)) => (command.as_str(), args),
+			None => return Ok(None),
+		};
+		let command = match command {
+			"list" => {
+				let mut section = None;
+				let mut search = None;
+				let mut args = args.iter();
+				while let Some(arg) = args.next() {
+					match arg.as_str() {
+						"--search" => {
+							search = Some(args.next().context("--search requires a query")?.clone())
+						}
+						_ if section.is_none() => section = Some(arg.clone()),
+						_ => bail!("unexpected argument '{}'\n\n{}", arg, USAGE),
+					}
+				}
+				Self::List { section, search }
+			}
+			"get" => match args {
+				[key] => Self::Get { key: key.clone() },
+				_ => bail!("get requires exactly one key\n\n{}", USAGE),
+			},
+			"set" => match args {
+				[key, value] => Self::Set {
+					key: key.clone(),
+					value: value.clone(),
+				},
+				_ => bail!("set requires a key and a value\n\n{}", USAGE),
+			},
+			"reset" if !args.is_empty() => Self::Reset {
+				keys: args.to_vec(),
+			},
+			"reset" => bail!("reset requires at least one key\n\n{}", USAGE),
+			"export" => match args {
+				[path, sections @ ..] => Self::Export {
+					path: path.into(),
+					sections: sections.to_vec(),
+				},
+				[] => bail!("export requires a file\n\n{}", USAGE),
+			},
+			"import" => match args {
+				[path] => Self::Import {
+					path: path.into(),
+					yes: false,
+				},
+				[path, yes] if yes == "--yes" => Self::Import {
+					path: path.into(),
+					yes: true,
+				},
+				_ => bail!("import requires a file\n\n{}", USAGE),
+			},
+			"profile" => Self::Profile(ProfileCommand::parse(args)?),
+			"--dump-layout" => match args {
+				[format] if format == "json" => Self::DumpLayout,
+				[format] => bail!("unknown layout format '{}', only json is supported", format),
+				_ => bail!("--dump-layout requires a format\n\n{}", USAGE),
+			},
+			"help" | "--help" | "-h" => Self::Help,
+			_ => return Ok(None),
+		};
+		Ok(Some(command))
+	}
+
+	pub fn run(self) -> Result<()> {
+		let config = Config::new();
+		match self {
+			Self::List { section, search } => list(&config, section.as_deref(), search.as_deref()),
+			Self::Get { key } => {
+				println!("{}", config.get(find_key(&key)?));
+				Ok(())
+			}
+			Self::Set { key, value } => {
+				let key = find_key(&key)?;
+				let value = key.parse(&value)?;
+				config.set(key, value, Source::CommandLine)
+			}
+			Self::Reset { keys } => {
+				for key in keys {
+					config.reset(find_key(&key)?, Source::CommandLine)?;
+				}
+				Ok(())
+			}
+			Self::Export { path, sections } => export(&config, &path, &sections),
+			Self::Import { path, yes } => import(&config, &path, yes),
+			Self::Profile(command) => command.run(&config),
+			Self::DumpLayout => {
+				let layout = dump_layout(&config);
+				println!("{}", serde_json::to_string_pretty(&layout)?);
+				Ok(())
+			}
+			Self::Help => {
+				println!("{}", USAGE);
+				Ok(())
+			}
+		}
+	}
+}
+
+impl ProfileCommand {
+	fn parse(args: &[String]) -> Result<Self> {
+		let (command, args) = match args.split_first() {
+			Some((command, args)) => (command.as_str(), args),
+			None => bail!("profile requires a command\n\n{}", USAGE),
+		};
+		Ok(match (command, args) {
+			("list", []) => Self::List,
+			("set", [name, values @ ..]) => Self::Set {
+				name: name.clone(),
+				values: values.to_vec(),
+			},
+			("unset", [name, keys @ ..]) if !keys.is_empty() => Self::Unset {
+				name: name.clone(),
+				keys: keys.to_vec(),
+			},
+			("delete", [name]) => Self::Delete { name: name.clone() },
+			("activate", [name]) => Self::Activate { name: name.clone() },
+			("deactivate", []) => Self::Deactivate,
+			_ => bail!("invalid profile command\n\n{}", USAGE),
+		})
+	}
+
+	fn run(self, config: &Config) -> Result<()> {
+		let profiles = Profiles::new(sections::all_keys());
+		match self {
+			Self::List => {
+				let active = profiles.active();
+				for profile in profiles.list()? {
+					let marker = if active.as_ref() == Some(&profile.name) {
+						" (active)"
+					} else {
+						""
+					};
+					println!("{}{}", profile.name, marker);
+					for (key, value) in &profile.values {
+						println!("    {} = {}", key, value);
+					}
+				}
+				Ok(())
+			}
+			Self::Set { name, values } => {
+				let mut profile = profiles.get(&name).unwrap_or(Profile {
+					name,
+					values: Vec::new(),
+				});
+				for assignment in values {
+					let (id, value) = assignment
+						.split_once('=')
+						.with_context(|| format!("expected KEY=VALUE, got '{}'", assignment))?;
+					let key = profiles.key(id).with_context(|| {
+						format!("unknown key '{}', see `cosmic-settings list`", id)
+					})?;
+					let value = key.parse(value)?;
+					match profile.values.iter_mut().find(|(known, _)| *known == key) {
+						Some((_, old)) => *old = value,
+						None => profile.values.push((key, value)),
+					}
+				}
+				profiles.save(config, &profile)
+			}
+			Self::Unset { name, keys } => {
+				let mut profile = profiles.get(&name)?;
+				for id in keys {
+					let count = profile.values.len();
+					profile.values.retain(|(key, _)| key.to_string() != id);
+					if profile.values.len() == count {
+						bail!("profile '{}' doesn't set '{}'", name, id);
+					}
+				}
+				profiles.save(config, &profile)
+			}
+			Self::Delete { name } => profiles.remove(config, &name),
+			Self::Activate { name } => profiles.activate(config, &profiles.get(&name)?),
+			Self::Deactivate => {
+				if profiles.active().is_none() {
+					bail!("no profile is active");
+				}
+				profiles.deactivate(config)
+			}
+		}
+	}
+}
+
+/// Returns the path of every page, such as `Desktop/Dock`, along with its groups.
+fn pages() -> Vec<(String, Vec<Box<dyn SettingsGroup>>)> {
+	let mut out = Vec::new();
+	for (name, layout) in sections::layouts() {
+		match layout {
+			SectionLayout::Single(groups) => out.push((name.to_string(), groups)),
+			SectionLayout::Multiple(pages) => {
+				for (page, groups) in pages {
+					out.push((format!("{}/{}", name, page), groups));
+				}
+			}
+		}
+	}
+	out
+}
+
+fn find_key(id: &str) -> Result<&'static Key> {
+	pages()
+		.iter()
+		.flat_map(|(_, groups)| groups.iter())
+		.flat_map(|group| group.keys().iter().copied())
+		.find(|key| key.to_string() == id)
+		.with_context(|| format!("unknown key '{}', see `cosmic-settings list`", id))
+}
+
+fn list(config: &Config, section: Option<&str>, search: Option<&str>) -> Result<()> {
+	let matcher = SkimMatcherV2::default();
+	let search = search.map(|search| search.trim().to_lowercase());
+	let mut found = false;
+	for (path, groups) in pages() {
+		if let Some(section) = section {
+			let path = path.to_lowercase();
+			let section = section.to_lowercase();
+			if path != section && !path.starts_with(&format!("{}/", section)) {
+				continue;
+			}
+		}
+		for group in groups {
+			let keys = group.keys();
+			if keys.is_empty() {
+				continue;
+			}
+			let title = group.title();
+			if let Some(search) = &search {
+				let matches = group
+					.keywords()
+					.iter()
+					.chain([&title])
+					.any(|needle| matcher.fuzzy_match(needle, search).is_some())
+					|| keys
+						.iter()
+						.any(|key| key.to_string().contains(search.as_str()));
+				if !matches {
+					continue;
+				}
+			}
+			found = true;
+			println!("{}: {}", path, title);
+			for key in keys {
+				let locked = match config.lock(key) {
+					Some(lock) => format!(", locked: {}", lock.describe()),
+					None => String::new(),
+				};
+				println!(
+					"    {} = {} ({}{})",
+					key,
+					config.get(key),
+					allowed(&key.kind),
+					locked
+				);
+			}
+		}
+	}
+	if !found {
+		bail!("no settings keys matched");
+	}
+	Ok(())
+}
+
+fn export(config: &Config, path: &Path, names: &[String]) -> Result<()> {
+	let mut sections = sections::keys();
+	if !names.is_empty() {
+		for name in names {
+			if !sections
+				.iter()
+				.any(|(section, _)| section.eq_ignore_ascii_case(name))
+			{
+				bail!("no section named '{}' has settings keys", name);
+			}
+		}
+		sections.retain(|(section, _)| names.iter().any(|name| section.eq_ignore_ascii_case(name)));
+	}
+	Bundle::export(config, &sections).save(path)?;
+	let count = sections.iter().map(|(_, keys)| keys.len()).sum::<usize>();
+	println!("Exported {} settings to {}", count, path.display());
+	Ok(())
+}
+
+fn import(config: &Config, path: &Path, yes: bool) -> Result<()> {
+	let bundle = Bundle::load(path)?;
+	let import = bundle.import(config, &sections::all_keys())?;
+	for key in &import.unknown {
+		eprintln!("warning: skipping unknown key '{}'", key);
+	}
+	for key in &import.locked {
+		eprintln!("warning: skipping locked key '{}'", key);
+	}
+	if import.changes.is_empty() {
+		println!("The settings already match {}", path.display());
+		return Ok(());
+	}
+	for change in &import.changes {
+		println!("    {} = {} → {}", change.key, change.old, change.new);
+	}
+	if !yes {
+		print!("Apply {} changes? [y/N] ", import.changes.len());
+		io::stdout().flush()?;
+		let mut answer = String::new();
+		io::stdin().lock().read_line(&mut answer)?;
+		if !matches!(answer.trim(), "y" | "Y" | "yes") {
+			bail!("import cancelled");
+		}
+	}
+	import.apply(config)
+}
+
+/// Describes the values a key accepts.
+fn allowed(kind: &KeyKind) -> String {
+	match kind {
+		KeyKind::Bool { .. } => "true, false".to_string(),
+		KeyKind::Int { min, max, .. } => format!("{} to {}", min, max),
+		KeyKind::Choice { choices, .. } => choices.join(", "),
+	}
+}
+
+/// Describes every section, page, group and setting along with the current values of their
+/// keys, for documentation and for checking changes to the layout.
+fn dump_layout(config: &Config) -> serde_json::Value {
+	let sections = sections::layouts()
+		.into_iter()
+		.map(|(name, layout)| {
+			let pages = match layout {
+				SectionLayout::Single(groups) => vec![dump_page(config, name, name, &groups)],
+				SectionLayout::Multiple(pages) => pages
+					.iter()
+					.map(|(page, groups)| {
+						dump_page(config, &format!("{}/{}", name, page), page, groups)
+					})
+					.collect(),
+			};
+			json!({
+				"name": name,
+				"title": tr(name),
+				"pages": pages,
+			})
+		})
+		.collect::<Vec<_>>();
+	json!({ "sections": sections })
+}
+
+fn dump_page(
+	config: &Config,
+	path: &str,
+	name: &str,
+	groups: &[Box<dyn SettingsGroup>],
+) -> serde_json::Value {
+	let groups = groups
+		.iter()
+		.map(|group| {
+			json!({
+				"title": group.title(),
+				"keywords": group.keywords(),
+				"keys": group.keys().iter().map(|key| dump_key(config, key)).collect::<Vec<_>>(),
+				"rows": group.rows().iter().map(|row| dump_row(config, row)).collect::<Vec<_>>(),
+			})
+		})
+		.collect::<Vec<_>>();
+	json!({
+		"path": path,
+		"title": tr(name),
+		"groups": groups,
+	})
+}
+
+fn dump_key(config: &Config, key: &Key) -> serde_json::Value {
+	let (kind, allowed) = match &key.kind {
+		KeyKind::Bool { .. } => ("bool", json!(null)),
+		KeyKind::Int { min, max, .. } => ("int", json!({ "min": min, "max": max })),
+		KeyKind::Choice { choices, .. } => ("choice", json!(choices)),
+	};
+	let lock = config.lock(key);
+	json!({
+		"key": key.to_string(),
+		"kind": kind,
+		"allowed": allowed,
+		"default": json_value(config.default_value(key)),
+		"value": json_value(config.get(key)),
+		"locked": lock.is_some(),
+		"reason": lock.and_then(|lock| lock.reason),
+	})
+}
+
+fn dump_row(config: &Config, row: &Row) -> serde_json::Value {
+	let mut out = json!({
+		"title": row.title,
+		"description": row.description,
+		"control": row.control.name(),
+		"key": row.key.map(Key::to_string),
+		"value": row.key.map(|key| json_value(config.get(key))),
+	});
+	match &row.control {
+		Control::Radio(options) | Control::Dropdown(options) => {
+			out["options"] = options
+				.iter()
+				.map(|(value, title)| json!({ "value": value, "title": title }))
+				.collect();
+		}
+		Control::Button { label, page } => {
+			out["label"] = json!(label);
+			out["page"] = json!(page);
+		}
+		Control::Switch | Control::Spin | Control::Text => {}
+	}
+	out
+}
+
+fn json_value(value: Value) -> serde_json::Value {
+	match value {
+		Value::Bool(value) => json!(value),
+		Value::Int(value) => json!(value),
+		Value::Choice(value) => json!(value),
+	}
+}
@@ -0,0 +1,329 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+pub mod bind;
+mod bundle;
+mod history;
+mod key;
+mod policy;
+mod profiles;
+mod watch;
+
+pub use self::{
+	bundle::{Bundle, Change, Import},
+	history::{Entry, History, Source},
+	key::{Key, KeyKind, Value},
+	policy::Lock,
+	profiles::{Profile, Profiles},
+};
+use self::{policy::Policy, watch::Watcher};
+use anyhow::{bail, Context, Result};
+use gtk4::glib;
+use std::{
+	cell::{Cell, RefCell},
+	collections::HashMap,
+	fs, io,
+	path::{Path, PathBuf},
+	rc::Rc,
+};
+
+type Subscriber = Box<dyn Fn(&Value)>;
+type Recorder = Box<dyn Fn(&Entry)>;
+
+/// The typed store that every bound settings widget reads from and writes to.
+///
+/// Each key is stored as a plain text file at `$XDG_CONFIG_HOME/cosmic/<component>/<name>`,
+/// so that components can read their settings without depending on this app. Keys that the
+/// user hasn't set fall back to the defaults of the system's [`Policy`], and keys it locks
+/// can't be changed.
+pub struct Config {
+	/// The directory containing the config directory of each component.
+	root: PathBuf,
+	/// The keys that have subscribers, by the path they are stored at.
+	keys: RefCell<HashMap<PathBuf, &'static Key>>,
+	/// The last known value of every key that has subscribers.
+	values: RefCell<HashMap<PathBuf, Value>>,
+	/// The callbacks to run when the value of a key changes.
+	subscribers: RefCell<HashMap<PathBuf, Vec<Subscriber>>>,
+	/// Whether subscribers are currently being told about a change.
+	///
+	/// Writes are ignored while this is set, so that a widget being updated to match a
+	/// change doesn't write that change back out again.
+	applying: Cell<bool>,
+	/// Watches for changes made outside of this app, once [`Config::watch`] has been called.
+	watcher: RefCell<Option<Watcher>>,
+	/// The defaults and locked keys set by administrators.
+	policy: Policy,
+	/// The log that every change is recorded in.
+	history: History,
+	/// The callbacks to run when a change is recorded in the history.
+	recorded: RefCell<Vec<Recorder>>,
+}
+
+impl Config {
+	pub fn new() -> Self {
+		Self::with_root(glib::user_config_dir().join("cosmic"))
+	}
+
+	pub fn with_root(root: PathBuf) -> Self {
+		Self {
+			root,
+			keys: RefCell::default(),
+			values: RefCell::default(),
+			subscribers: RefCell::default(),
+			applying: Cell::new(false),
+			watcher: RefCell::default(),
+			policy: Policy::load(),
+			history: History::new(),
+			recorded: RefCell::default(),
+		}
+	}
+
+	/// Returns the log that every change is recorded in.
+	pub fn history(&self) -> &History {
+		&self.history
+	}
+
+	/// Returns the path of the file the given key is stored in.
+	pub fn path(&self, key: &Key) -> PathBuf {
+		self.root.join(key.component).join(key.name)
+	}
+
+	/// Reads the current value of a key, which is its locked value if it's locked, and
+	/// otherwise falls back to its default if it is unset or invalid.
+	pub fn get(&self, key: &Key) -> Value {
+		match self.policy.lock(key) {
+			Some(lock) => lock.value,
+			None => self.read(key),
+		}
+	}
+
+	/// Returns the value a key has when the user hasn't set it.
+	pub fn default_value(&self, key: &Key) -> Value {
+		self.policy.default_value(key)
+	}
+
+	/// Returns the lock on a key, if an administrator has locked it.
+	pub fn lock(&self, key: &Key) -> Option<Lock> {
+		self.policy.lock(key)
+	}
+
+	/// Validates and stores a new value for a key, recording the change in the history.
+	///
+	/// Fails if the key is locked.
+	pub fn set(&self, key: &Key, value: Value, source: Source) -> Result<()> {
+		if self.applying.get() {
+			return Ok(());
+		}
+		self.check_unlocked(key)?;
+		key.validate(&value)?;
+		self.write(key, value, source)
+	}
+
+	/// Stores the locked value of every given key that is locked, so that components that
+	/// only read the user's config see it too.
+	pub fn enforce_policy(&self, keys: &[&'static Key]) {
+		for key in keys {
+			if let Some(lock) = self.policy.lock(key) {
+				if let Err(err) = self.write(key, lock.value, Source::Policy) {
+					error!(?err, %key, "Failed to store locked setting");
+				}
+			}
+		}
+	}
+
+	/// Removes the stored value of a key, so that it reads as its default again.
+	///
+	/// Fails if the key is locked.
+	pub fn reset(&self, key: &Key, source: Source) -> Result<()> {
+		if self.applying.get() {
+			return Ok(());
+		}
+		self.check_unlocked(key)?;
+		let old = self.get(key);
+		let path = self.path(key);
+		match fs::remove_file(&path) {
+			Ok(()) => {}
+			Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+			Err(err) => {
+				return Err(err).with_context(|| format!("failed to remove '{}'", path.display()))
+			}
+		}
+		let value = self.default_value(key);
+		if old != value {
+			self.record(key, source, &old, &value);
+		}
+		self.changed(&path, value);
+		Ok(())
+	}
+
+	/// Restores the value a key had before the given change, given every key this app knows
+	/// about, since the page of the key may not have been built yet.
+	pub fn revert(&self, entry: &Entry, keys: &[&'static Key]) -> Result<()> {
+		let key = keys
+			.iter()
+			.find(|key| key.to_string() == entry.key)
+			.copied()
+			.with_context(|| format!("unknown key '{}'", entry.key))?;
+		let value = key.parse(&entry.old)?;
+		self.set(key, value, Source::Revert)
+	}
+
+	/// Runs the given callback every time a change is recorded in the history.
+	pub fn connect_recorded<F: Fn(&Entry) + 'static>(&self, callback: F) {
+		self.recorded.borrow_mut().push(Box::new(callback));
+	}
+
+	/// Runs the given callback every time the value of a key changes, whether that change
+	/// was made by this app or by something else.
+	pub fn subscribe<F: Fn(&Value) + 'static>(&self, key: &'static Key, callback: F) {
+		let path = self.path(key);
+		if let Some(watcher) = self.watcher.borrow_mut().as_mut() {
+			watcher.watch(path.parent().expect("key path has no parent"));
+		}
+		self.values
+			.borrow_mut()
+			.entry(path.clone())
+			.or_insert_with(|| self.get(key));
+		self.keys.borrow_mut().insert(path.clone(), key);
+		self.subscribers
+			.borrow_mut()
+			.entry(path)
+			.or_default()
+			.push(Box::new(callback));
+	}
+
+	/// Starts watching the config directories of subscribed keys for changes made outside
+	/// of this app, and passes those changes on to subscribers from the GTK main loop.
+	pub fn watch(self: &Rc<Self>) {
+		let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+		let mut watcher = match Watcher::new(tx) {
+			Ok(watcher) => watcher,
+			Err(err) => {
+				error!(%err, "Failed to set up config watcher");
+				return;
+			}
+		};
+		for path in self.keys.borrow().keys() {
+			watcher.watch(path.parent().expect("key path has no parent"));
+		}
+		*self.watcher.borrow_mut() = Some(watcher);
+
+		let config = Rc::downgrade(self);
+		crate::task::spawn_local(async move {
+			while let Some(path) = rx.recv().await {
+				match config.upgrade() {
+					Some(config) => config.reload(&path),
+					None => break,
+				}
+			}
+		});
+	}
+
+	/// Reads the value that the user has stored for a key, ignoring any lock.
+	fn read(&self, key: &Key) -> Value {
+		match fs::read_to_string(self.path(key)) {
+			Ok(text) => match key.parse(text.trim()) {
+				Ok(value) => value,
+				Err(err) => {
+					warn!(%err, %key, "Ignoring invalid stored value");
+					self.default_value(key)
+				}
+			},
+			Err(err) if err.kind() == io::ErrorKind::NotFound => self.default_value(key),
+			Err(err) => {
+				error!(%err, %key, "Failed to read setting");
+				self.default_value(key)
+			}
+		}
+	}
+
+	fn check_unlocked(&self, key: &Key) -> Result<()> {
+		match self.policy.lock(key) {
+			Some(Lock {
+				reason: Some(reason),
+				..
+			}) => bail!("'{}' is locked by your administrator: {}", key, reason),
+			Some(_) => bail!("'{}' is locked by your administrator", key),
+			None => Ok(()),
+		}
+	}
+
+	/// Stores a value that has been validated, recording the change in the history.
+	fn write(&self, key: &Key, value: Value, source: Source) -> Result<()> {
+		let old = self.read(key);
+		if old == value {
+			return Ok(());
+		}
+		let path = self.path(key);
+		let dir = path.parent().expect("key path has no parent");
+		fs::create_dir_all(dir).with_context(|| format!("failed to create '{}'", dir.display()))?;
+		// Write to a temporary file first, so readers never see a partially written value.
+		let tmp = dir.join(format!(".{}.tmp", key.name));
+		fs::write(&tmp, value.to_string())
+			.with_context(|| format!("failed to write '{}'", tmp.display()))?;
+		fs::rename(&tmp, &path)
+			.with_context(|| format!("failed to replace '{}'", path.display()))?;
+		self.record(key, source, &old, &value);
+		self.changed(&path, value);
+		Ok(())
+	}
+
+	/// Re-reads a file that was changed on disk, and passes its new value on to subscribers.
+	fn reload(&self, path: &Path) {
+		let key = match self.keys.borrow().get(path) {
+			Some(key) => *key,
+			None => return,
+		};
+		if let Some(lock) = self.policy.lock(key) {
+			// A locked key was changed by hand, so put its locked value back.
+			if let Err(err) = self.write(key, lock.value, Source::Policy) {
+				error!(?err, %key, "Failed to restore locked setting");
+			}
+			return;
+		}
+		let value = self.get(key);
+		let old = match self.values.borrow().get(path) {
+			Some(old) if *old != value => old.clone(),
+			_ => return,
+		};
+		debug!(%key, %value, "Setting changed on disk");
+		self.record(key, Source::External, &old, &value);
+		self.changed(path, value);
+	}
+
+	/// Appends a change to the history, and tells anything watching the history about it.
+	fn record(&self, key: &Key, source: Source, old: &Value, new: &Value) {
+		let entry = Entry::new(source, key.to_string(), old.to_string(), new.to_string());
+		if let Err(err) = self.history.record(&entry) {
+			error!(?err, %key, "Failed to record setting change");
+		}
+		for callback in self.recorded.borrow().iter() {
+			callback(&entry);
+		}
+	}
+
+	/// Records the new value of a key, and tells subscribers about it if it differs from
+	/// the last known value.
+	fn changed(&self, path: &Path, value: Value) {
+		{
+			let mut values = self.values.borrow_mut();
+			match values.get_mut(path) {
+				Some(old) if *old != value => *old = value.clone(),
+				_ => return,
+			}
+		}
+		if let Some(subscribers) = self.subscribers.borrow().get(path) {
+			self.applying.set(true);
+			for subscriber in subscribers {
+				subscriber(&value);
+			}
+			self.applying.set(false);
+		}
+	}
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self::new()
+	}
+}
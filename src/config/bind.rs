@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Helpers that connect settings widgets to keys in the [`Config`] store.
+//!
+//! Bound widgets are kept up to date when their key is changed elsewhere, such as by
+//! another widget bound to the same key or by another program writing to the config file.
+
+use super::{Config, Key, KeyKind, Source, Value};
+use crate::a11y;
+use gtk4::{
+	glib::{self, clone},
+	prelude::*,
+	CheckButton, DropDown, SpinButton, Switch, Widget,
+};
+use std::rc::Rc;
+
+/// Binds a [`Switch`] to a boolean key.
+pub fn switch(config: &Rc<Config>, switch: &Switch, key: &'static Key) {
+	switch.set_active(config.get(key).as_bool().unwrap_or_default());
+	switch.connect_active_notify(clone!(@strong config => move |switch| {
+		store(&config, key, Value::Bool(switch.is_active()));
+	}));
+	show_lock(config, switch.upcast_ref(), key);
+	let switch = switch.downgrade();
+	config.subscribe(key, move |value| {
+		if let (Some(switch), Some(active)) = (switch.upgrade(), value.as_bool()) {
+			switch.set_active(active);
+		}
+	});
+}
+
+/// Binds a group of radio [`CheckButton`]s to a choice key, with each button standing for
+/// the choice it is paired with.
+pub fn radio(config: &Rc<Config>, buttons: &[(&CheckButton, &'static str)], key: &'static Key) {
+	let current = config.get(key);
+	let mut choices = Vec::with_capacity(buttons.len());
+	for &(button, choice) in buttons {
+		button.set_active(current.as_choice() == Some(choice));
+		button.connect_toggled(clone!(@strong config => move |button| {
+			if button.is_active() {
+				store(&config, key, Value::Choice(choice.to_string()));
+			}
+		}));
+		show_lock(config, button.upcast_ref(), key);
+		choices.push((button.downgrade(), choice));
+	}
+	config.subscribe(key, move |value| {
+		for (button, choice) in &choices {
+			if let Some(button) = button.upgrade() {
+				if value.as_choice() == Some(*choice) {
+					button.set_active(true);
+				}
+			}
+		}
+	});
+}
+
+/// Binds a [`DropDown`] to a choice key, with each item standing for the choice at the same
+/// position.
+pub fn dropdown(config: &Rc<Config>, dropdown: &DropDown, key: &'static Key) {
+	let choices = match key.kind {
+		KeyKind::Choice { choices, .. } => choices,
+		_ => &[],
+	};
+	let position = move |value: &Value| {
+		value
+			.as_choice()
+			.and_then(|choice| choices.iter().position(|other| *other == choice))
+	};
+	if let Some(position) = position(&config.get(key)) {
+		dropdown.set_selected(position as u32);
+	}
+	dropdown.connect_selected_notify(clone!(@strong config => move |dropdown| {
+		if let Some(choice) = choices.get(dropdown.selected() as usize) {
+			store(&config, key, Value::Choice(choice.to_string()));
+		}
+	}));
+	show_lock(config, dropdown.upcast_ref(), key);
+	let dropdown = dropdown.downgrade();
+	config.subscribe(key, move |value| {
+		if let (Some(dropdown), Some(position)) = (dropdown.upgrade(), position(value)) {
+			dropdown.set_selected(position as u32);
+		}
+	});
+}
+
+/// Binds a [`SpinButton`] to an integer key.
+pub fn spin(config: &Rc<Config>, spin: &SpinButton, key: &'static Key) {
+	spin.set_value(config.get(key).as_int().unwrap_or_default() as f64);
+	spin.connect_value_changed(clone!(@strong config => move |spin| {
+		store(&config, key, Value::Int(spin.value_as_int().into()));
+	}));
+	show_lock(config, spin.upcast_ref(), key);
+	let spin = spin.downgrade();
+	config.subscribe(key, move |value| {
+		if let (Some(spin), Some(int)) = (spin.upgrade(), value.as_int()) {
+			spin.set_value(int as f64);
+		}
+	});
+}
+
+/// Makes the widget of a key that an administrator has locked insensitive, with the reason
+/// it's locked as its tooltip.
+fn show_lock(config: &Config, widget: &Widget, key: &Key) {
+	if let Some(lock) = config.lock(key) {
+		let reason = lock.describe();
+		widget.set_sensitive(false);
+		widget.set_tooltip_text(Some(&reason));
+		a11y::set_description(widget, &reason);
+	}
+}
+
+fn store(config: &Config, key: &Key, value: Value) {
+	if let Err(err) = config.set(key, value, Source::App) {
+		error!(?err, %key, "Failed to store setting");
+	}
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{Config, Key, Source, Value};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The version of the bundle format that this app writes. Bundles written by newer versions
+/// are refused, since they may not mean the same thing.
+const VERSION: u32 = 1;
+
+/// The values of settings keys, written to a file so that they can be applied on another
+/// machine, such as:
+///
+/// ```toml
+/// version = 1
+/// sections = ["Desktop"]
+///
+/// [settings.cosmic-dock]
+/// size = "large"
+/// visibility = "always-hide"
+/// ```
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bundle {
+	version: u32,
+	/// The sections the settings were exported from, by their English name.
+	#[serde(default)]
+	pub sections: Vec<String>,
+	/// The value of every key, by component and then name.
+	#[serde(default)]
+	settings: BTreeMap<String, BTreeMap<String, toml::Value>>,
+}
+
+/// A key whose value differs from that in a [`Bundle`].
+#[derive(Debug)]
+pub struct Change {
+	pub key: &'static Key,
+	/// The current value.
+	pub old: Value,
+	/// The value from the bundle.
+	pub new: Value,
+}
+
+/// The changes that importing a [`Bundle`] makes.
+#[derive(Debug, Default)]
+pub struct Import {
+	pub changes: Vec<Change>,
+	/// The keys in the bundle that this app doesn't know about, such as those of panels that
+	/// aren't installed, which are left out.
+	pub unknown: Vec<String>,
+	/// The keys in the bundle that an administrator has locked, which are left out.
+	pub locked: Vec<&'static Key>,
+}
+
+impl Bundle {
+	/// Captures the current values of the keys of the given sections, which are given by their
+	/// English name.
+	pub fn export(config: &Config, sections: &[(&str, Vec<&'static Key>)]) -> Self {
+		let mut settings = BTreeMap::<_, BTreeMap<_, _>>::new();
+		for key in sections.iter().flat_map(|(_, keys)| keys) {
+			settings
+				.entry(key.component.to_string())
+				.or_default()
+				.insert(key.name.to_string(), config.get(key).to_toml());
+		}
+		Self {
+			version: VERSION,
+			sections: sections.iter().map(|(name, _)| name.to_string()).collect(),
+			settings,
+		}
+	}
+
+	pub fn load(path: &Path) -> Result<Self> {
+		let text = fs::read_to_string(path)
+			.with_context(|| format!("failed to read '{}'", path.display()))?;
+		let bundle: Self = toml::from_str(&text)
+			.with_context(|| format!("'{}' is not a settings bundle", path.display()))?;
+		if bundle.version > VERSION {
+			bail!(
+				"'{}' was exported by a newer version of COSMIC Settings",
+				path.display()
+			);
+		}
+		Ok(bundle)
+	}
+
+	pub fn save(&self, path: &Path) -> Result<()> {
+		let text = toml::to_string(self).context("failed to serialize settings bundle")?;
+		fs::write(path, text).with_context(|| format!("failed to write '{}'", path.display()))
+	}
+
+	/// Compares the bundle against the current values, given every key this app knows about.
+	pub fn import(&self, config: &Config, keys: &[&'static Key]) -> Result<Import> {
+		let mut import = Import::default();
+		for (component, values) in &self.settings {
+			for (name, value) in values {
+				let key = match keys
+					.iter()
+					.find(|key| key.component == component.as_str() && key.name == name.as_str())
+				{
+					Some(key) => *key,
+					None => {
+						import.unknown.push(format!("{}/{}", component, name));
+						continue;
+					}
+				};
+				if config.lock(key).is_some() {
+					import.locked.push(key);
+					continue;
+				}
+				let new = key.parse_toml(value)?;
+				let old = config.get(key);
+				if old != new {
+					import.changes.push(Change { key, old, new });
+				}
+			}
+		}
+		Ok(import)
+	}
+}
+
+impl Import {
+	/// Stores the values from the bundle, recording each change in the history.
+	pub fn apply(&self, config: &Config) -> Result<()> {
+		for change in &self.changes {
+			config.set(change.key, change.new.clone(), Source::Import)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::KeyKind;
+	use std::path::PathBuf;
+
+	static SIZE: Key = Key {
+		component: "cosmic-dock",
+		name: "size",
+		kind: KeyKind::Choice {
+			default: "medium",
+			choices: &["small", "medium", "large"],
+		},
+	};
+	static AUTOHIDE: Key = Key {
+		component: "cosmic-dock",
+		name: "autohide",
+		kind: KeyKind::Bool { default: false },
+	};
+	static HOT_CORNER: Key = Key {
+		component: "cosmic-comp",
+		name: "hot-corner",
+		kind: KeyKind::Bool { default: true },
+	};
+	static KEYS: [&Key; 3] = [&SIZE, &AUTOHIDE, &HOT_CORNER];
+
+	/// A config stored in its own temporary directory, which locks the hot corner.
+	struct TestConfig {
+		root: PathBuf,
+		config: Config,
+	}
+
+	impl TestConfig {
+		fn new(name: &str) -> Self {
+			let root = std::env::temp_dir().join(format!(
+				"cosmic-settings-bundle-{}-{}",
+				name,
+				std::process::id()
+			));
+			let mut config = Config::with_root(root.clone());
+			config.policy = toml::from_str(
+				r#"
+				[locked."cosmic-comp/hot-corner"]
+				value = false
+				"#,
+			)
+			.unwrap();
+			fs::create_dir_all(root.join("cosmic-dock")).unwrap();
+			fs::write(root.join("cosmic-dock").join("size"), "large").unwrap();
+			Self { root, config }
+		}
+	}
+
+	impl Drop for TestConfig {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.root);
+		}
+	}
+
+	fn bundle(text: &str) -> Bundle {
+		toml::from_str(text).unwrap()
+	}
+
+	#[test]
+	fn import_diff() {
+		let test = TestConfig::new("diff");
+		let bundle = bundle(
+			r#"
+			version = 1
+
+			[settings.cosmic-dock]
+			size = "large"
+			autohide = true
+
+			[settings.cosmic-comp]
+			hot-corner = true
+
+			[settings.cosmic-panel]
+			size = "small"
+			"#,
+		);
+		let import = bundle.import(&test.config, &KEYS).unwrap();
+
+		assert_eq!(import.changes.len(), 1);
+		let change = &import.changes[0];
+		assert!(std::ptr::eq(change.key, &AUTOHIDE));
+		assert_eq!(change.old, Value::Bool(false));
+		assert_eq!(change.new, Value::Bool(true));
+
+		assert_eq!(import.unknown, ["cosmic-panel/size"]);
+		assert_eq!(import.locked.len(), 1);
+		assert!(std::ptr::eq(import.locked[0], &HOT_CORNER));
+	}
+
+	#[test]
+	fn import_invalid_value() {
+		let test = TestConfig::new("invalid");
+		let bundle = bundle(
+			r#"
+			version = 1
+
+			[settings.cosmic-dock]
+			size = "huge"
+			"#,
+		);
+		assert!(bundle.import(&test.config, &KEYS).is_err());
+	}
+
+	#[test]
+	fn export_round_trip() {
+		let test = TestConfig::new("export");
+		let bundle = Bundle::export(&test.config, &[("Desktop", vec![&SIZE, &AUTOHIDE])]);
+		assert_eq!(bundle.sections, ["Desktop"]);
+
+		let path = test.root.join("bundle.toml");
+		bundle.save(&path).unwrap();
+		let bundle = Bundle::load(&path).unwrap();
+		let import = bundle.import(&test.config, &KEYS).unwrap();
+		assert!(import.changes.is_empty());
+		assert!(import.unknown.is_empty());
+		assert!(import.locked.is_empty());
+	}
+
+	#[test]
+	fn load_newer_version() {
+		let test = TestConfig::new("version");
+		let path = test.root.join("bundle.toml");
+		fs::write(&path, format!("version = {}", VERSION + 1)).unwrap();
+		assert!(Bundle::load(&path).is_err());
+	}
+}
@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use anyhow::{bail, Context, Result};
+use gtk4::glib;
+use std::{
+	fmt,
+	fs::{self, OpenOptions},
+	io::{self, Write},
+	path::PathBuf,
+	str::FromStr,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+/// What caused a setting to change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+	/// A widget in this app was changed.
+	App,
+	/// The command line interface was used.
+	CommandLine,
+	/// Another program changed the config file.
+	External,
+	/// A group was reset to its defaults.
+	Reset,
+	/// An earlier change was reverted from the history.
+	Revert,
+	/// A bundle of settings was imported.
+	Import,
+	/// A profile was activated or deactivated.
+	Profile,
+	/// A key was locked by the system's policy.
+	Policy,
+}
+
+impl fmt::Display for Source {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::App => "app",
+			Self::CommandLine => "cli",
+			Self::External => "external",
+			Self::Reset => "reset",
+			Self::Revert => "revert",
+			Self::Import => "import",
+			Self::Profile => "profile",
+			Self::Policy => "policy",
+		})
+	}
+}
+
+impl FromStr for Source {
+	type Err = anyhow::Error;
+
+	fn from_str(source: &str) -> Result<Self> {
+		Ok(match source {
+			"app" => Self::App,
+			"cli" => Self::CommandLine,
+			"external" => Self::External,
+			"reset" => Self::Reset,
+			"revert" => Self::Revert,
+			"import" => Self::Import,
+			"profile" => Self::Profile,
+			"policy" => Self::Policy,
+			_ => bail!("unknown change source '{}'", source),
+		})
+	}
+}
+
+/// A single recorded change of a setting.
+#[derive(Debug, Clone)]
+pub struct Entry {
+	/// When the change happened, in seconds since the Unix epoch.
+	pub time: i64,
+	/// What caused the change.
+	pub source: Source,
+	/// The key that was changed, as `component/name`.
+	pub key: String,
+	/// The value before the change.
+	pub old: String,
+	/// The value after the change.
+	pub new: String,
+}
+
+impl Entry {
+	pub fn new(source: Source, key: String, old: String, new: String) -> Self {
+		let time = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|time| time.as_secs() as i64)
+			.unwrap_or_default();
+		Self {
+			time,
+			source,
+			key,
+			old,
+			new,
+		}
+	}
+
+	/// Returns the time of this change, formatted for the current locale.
+	pub fn local_time(&self) -> String {
+		glib::DateTime::from_unix_local(self.time)
+			.and_then(|time| time.format("%x %X"))
+			.map(|time| time.to_string())
+			.unwrap_or_else(|_| self.time.to_string())
+	}
+}
+
+impl fmt::Display for Entry {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}\t{}\t{}\t{}\t{}",
+			self.time, self.source, self.key, self.old, self.new
+		)
+	}
+}
+
+impl FromStr for Entry {
+	type Err = anyhow::Error;
+
+	fn from_str(line: &str) -> Result<Self> {
+		let mut fields = line.split('\t');
+		let mut field = || fields.next().context("history entry is missing fields");
+		Ok(Self {
+			time: field()?.parse().context("invalid history entry time")?,
+			source: field()?.parse()?,
+			key: field()?.to_string(),
+			old: field()?.to_string(),
+			new: field()?.to_string(),
+		})
+	}
+}
+
+/// The persistent, timestamped log of every setting change.
+///
+/// Entries are appended one per line to `$XDG_DATA_HOME/cosmic-settings/history`.
+pub struct History {
+	path: PathBuf,
+}
+
+impl History {
+	pub fn new() -> Self {
+		Self::with_path(
+			glib::user_data_dir()
+				.join("cosmic-settings")
+				.join("history"),
+		)
+	}
+
+	/// Creates a log stored in the given file instead.
+	pub fn with_path(path: PathBuf) -> Self {
+		Self { path }
+	}
+
+	/// Appends a change to the end of the log.
+	pub fn record(&self, entry: &Entry) -> Result<()> {
+		let dir = self.path.parent().expect("history path has no parent");
+		fs::create_dir_all(dir).with_context(|| format!("failed to create '{}'", dir.display()))?;
+		let mut file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&self.path)
+			.with_context(|| format!("failed to open '{}'", self.path.display()))?;
+		writeln!(file, "{}", entry)
+			.with_context(|| format!("failed to write '{}'", self.path.display()))
+	}
+
+	/// Returns every recorded change, from oldest to newest.
+	pub fn entries(&self) -> Vec<Entry> {
+		let text = match fs::read_to_string(&self.path) {
+			Ok(text) => text,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return Vec::new(),
+			Err(err) => {
+				error!(%err, "Failed to read settings history");
+				return Vec::new();
+			}
+		};
+		text.lines()
+			.filter_map(|line| match line.parse() {
+				Ok(entry) => Some(entry),
+				Err(err) => {
+					warn!(?err, %line, "Ignoring invalid history entry");
+					None
+				}
+			})
+			.collect()
+	}
+}
+
+impl Default for History {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn entry(source: Source, new: &str) -> Entry {
+		Entry {
+			time: 1_650_000_000,
+			source,
+			key: "cosmic-dock/size".to_string(),
+			old: "medium".to_string(),
+			new: new.to_string(),
+		}
+	}
+
+	fn assert_same(a: &Entry, b: &Entry) {
+		assert_eq!(a.time, b.time);
+		assert_eq!(a.source, b.source);
+		assert_eq!(a.key, b.key);
+		assert_eq!(a.old, b.old);
+		assert_eq!(a.new, b.new);
+	}
+
+	#[test]
+	fn entry_round_trip() {
+		for source in [
+			Source::App,
+			Source::CommandLine,
+			Source::External,
+			Source::Reset,
+			Source::Revert,
+			Source::Import,
+			Source::Profile,
+			Source::Policy,
+		] {
+			let entry = entry(source, "large");
+			assert_same(&entry.to_string().parse().unwrap(), &entry);
+		}
+	}
+
+	#[test]
+	fn invalid_entries() {
+		assert!("".parse::<Entry>().is_err());
+		assert!("1650000000\tapp\tcosmic-dock/size\tmedium"
+			.parse::<Entry>()
+			.is_err());
+		assert!("yesterday\tapp\tcosmic-dock/size\tmedium\tlarge"
+			.parse::<Entry>()
+			.is_err());
+		assert!("1650000000\tmagic\tcosmic-dock/size\tmedium\tlarge"
+			.parse::<Entry>()
+			.is_err());
+	}
+
+	#[test]
+	fn record_and_read_back() {
+		let dir =
+			std::env::temp_dir().join(format!("cosmic-settings-history-{}", std::process::id()));
+		let history = History::with_path(dir.join("history"));
+		assert!(history.entries().is_empty());
+
+		let entries = [entry(Source::App, "large"), entry(Source::Revert, "small")];
+		for entry in &entries {
+			history.record(entry).unwrap();
+		}
+		// Lines that can't be parsed are skipped rather than losing the whole history.
+		fs::OpenOptions::new()
+			.append(true)
+			.open(&history.path)
+			.and_then(|mut file| writeln!(file, "garbage"))
+			.unwrap();
+		history.record(&entries[0]).unwrap();
+
+		let read = history.entries();
+		assert_eq!(read.len(), 3);
+		assert_same(&read[0], &entries[0]);
+		assert_same(&read[1], &entries[1]);
+		assert_same(&read[2], &entries[0]);
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
+}
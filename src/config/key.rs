@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use anyhow::{bail, Result};
+use std::fmt;
+
+/// A single typed setting, owned by the component that reads it.
+#[derive(Debug)]
+pub struct Key {
+	/// The component that reads this setting, such as `cosmic-dock`.
+	pub component: &'static str,
+	/// The name of this setting within its component.
+	pub name: &'static str,
+	/// The type of this setting, along with its default value.
+	pub kind: KeyKind,
+}
+
+/// The type of a [`Key`], along with its default value.
+#[derive(Debug)]
+pub enum KeyKind {
+	/// A setting that is either on or off.
+	Bool { default: bool },
+	/// A whole number within an inclusive range.
+	Int { default: i64, min: i64, max: i64 },
+	/// One of a fixed set of named choices.
+	Choice {
+		default: &'static str,
+		choices: &'static [&'static str],
+	},
+}
+
+/// The value of a [`Key`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+	Bool(bool),
+	Int(i64),
+	Choice(String),
+}
+
+impl Key {
+	/// Returns the value this key has when nothing has been stored for it.
+	pub fn default_value(&self) -> Value {
+		match self.kind {
+			KeyKind::Bool { default } => Value::Bool(default),
+			KeyKind::Int { default, .. } => Value::Int(default),
+			KeyKind::Choice { default, .. } => Value::Choice(default.to_string()),
+		}
+	}
+
+	/// Parses a value of this key from its textual form.
+	pub fn parse(&self, text: &str) -> Result<Value> {
+		let value = match self.kind {
+			KeyKind::Bool { .. } => match text {
+				"true" => Value::Bool(true),
+				"false" => Value::Bool(false),
+				_ => bail!("'{}' expects 'true' or 'false', got '{}'", self, text),
+			},
+			KeyKind::Int { .. } => match text.parse() {
+				Ok(int) => Value::Int(int),
+				Err(_) => bail!("'{}' expects a whole number, got '{}'", self, text),
+			},
+			KeyKind::Choice { .. } => Value::Choice(text.to_string()),
+		};
+		self.validate(&value)?;
+		Ok(value)
+	}
+
+	/// Ensures that a value has the type of this key, and lies within its allowed values.
+	pub fn validate(&self, value: &Value) -> Result<()> {
+		match (&self.kind, value) {
+			(KeyKind::Bool { .. }, Value::Bool(_)) => Ok(()),
+			(KeyKind::Int { min, max, .. }, Value::Int(int)) => {
+				if (*min..=*max).contains(int) {
+					Ok(())
+				} else {
+					bail!(
+						"'{}' must be between {} and {}, got {}",
+						self,
+						min,
+						max,
+						int
+					)
+				}
+			}
+			(KeyKind::Choice { choices, .. }, Value::Choice(choice)) => {
+				if choices.contains(&choice.as_str()) {
+					Ok(())
+				} else {
+					bail!(
+						"'{}' must be one of {}, got '{}'",
+						self,
+						choices.join(", "),
+						choice
+					)
+				}
+			}
+			_ => bail!("'{}' expects {}, got {:?}", self, self.kind, value),
+		}
+	}
+}
+
+impl Key {
+	/// Reads a value of this key from a TOML file, such as a bundle or a profile.
+	pub fn parse_toml(&self, value: &toml::Value) -> Result<Value> {
+		match value {
+			toml::Value::String(text) => self.parse(text),
+			value => self.parse(&value.to_string()),
+		}
+	}
+}
+
+impl fmt::Display for Key {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}/{}", self.component, self.name)
+	}
+}
+
+impl fmt::Display for KeyKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Bool { .. } => f.write_str("a boolean"),
+			Self::Int { .. } => f.write_str("a whole number"),
+			Self::Choice { .. } => f.write_str("a choice"),
+		}
+	}
+}
+
+impl Value {
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Self::Bool(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	pub fn as_int(&self) -> Option<i64> {
+		match self {
+			Self::Int(value) => Some(*value),
+			_ => None,
+		}
+	}
+
+	pub fn as_choice(&self) -> Option<&str> {
+		match self {
+			Self::Choice(value) => Some(value.as_str()),
+			_ => None,
+		}
+	}
+
+	/// Converts the value for writing to a TOML file, such as a bundle or a profile.
+	pub fn to_toml(&self) -> toml::Value {
+		match self {
+			Self::Bool(value) => toml::Value::Boolean(*value),
+			Self::Int(value) => toml::Value::Integer(*value),
+			Self::Choice(value) => toml::Value::String(value.clone()),
+		}
+	}
+}
+
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Bool(value) => value.fmt(f),
+			Self::Int(value) => value.fmt(f),
+			Self::Choice(value) => value.fmt(f),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ENABLED: Key = Key {
+		component: "cosmic-test",
+		name: "enabled",
+		kind: KeyKind::Bool { default: false },
+	};
+	const SIZE: Key = Key {
+		component: "cosmic-test",
+		name: "size",
+		kind: KeyKind::Int {
+			default: 4,
+			min: 1,
+			max: 8,
+		},
+	};
+	const POSITION: Key = Key {
+		component: "cosmic-test",
+		name: "position",
+		kind: KeyKind::Choice {
+			default: "bottom",
+			choices: &["top", "bottom"],
+		},
+	};
+
+	#[test]
+	fn parse_bool() {
+		assert_eq!(ENABLED.parse("true").unwrap(), Value::Bool(true));
+		assert_eq!(ENABLED.parse("false").unwrap(), Value::Bool(false));
+		assert!(ENABLED.parse("yes").is_err());
+	}
+
+	#[test]
+	fn parse_int() {
+		assert_eq!(SIZE.parse("8").unwrap(), Value::Int(8));
+		assert!(SIZE.parse("9").is_err());
+		assert!(SIZE.parse("0").is_err());
+		assert!(SIZE.parse("large").is_err());
+	}
+
+	#[test]
+	fn parse_choice() {
+		assert_eq!(
+			POSITION.parse("top").unwrap(),
+			Value::Choice("top".to_string())
+		);
+		assert!(POSITION.parse("left").is_err());
+	}
+
+	#[test]
+	fn validate_rejects_other_types() {
+		assert!(ENABLED.validate(&Value::Int(1)).is_err());
+		assert!(SIZE.validate(&Value::Choice("4".to_string())).is_err());
+		assert!(POSITION.validate(&Value::Bool(true)).is_err());
+	}
+
+	#[test]
+	fn toml_round_trip() {
+		for (key, value) in [
+			(&ENABLED, Value::Bool(true)),
+			(&SIZE, Value::Int(2)),
+			(&POSITION, Value::Choice("top".to_string())),
+		] {
+			assert_eq!(key.parse_toml(&value.to_toml()).unwrap(), value);
+		}
+	}
+
+	#[test]
+	fn text_round_trip() {
+		for key in [&ENABLED, &SIZE, &POSITION] {
+			let value = key.default_value();
+			assert_eq!(key.parse(&value.to_string()).unwrap(), value);
+		}
+	}
+}
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{Key, Value};
+use crate::i18n::tr;
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+/// Where administrators set the policy for every user of the system.
+const PATH: &str = "/etc/cosmic-settings/policy.toml";
+/// Overrides [`PATH`] in debug builds, such as to try out a policy without installing it.
+///
+/// Release builds ignore it, since any user could point it at an empty policy.
+#[cfg(debug_assertions)]
+const PATH_ENV: &str = "COSMIC_SETTINGS_POLICY";
+
+/// A key that an administrator has locked to a value, which users can't change.
+#[derive(Debug, Clone)]
+pub struct Lock {
+	pub value: Value,
+	/// Why the key is locked, to show next to it.
+	pub reason: Option<String>,
+}
+
+impl Lock {
+	/// Describes why the key is locked, falling back to a generic reason.
+	pub fn describe(&self) -> String {
+		self.reason
+			.clone()
+			.unwrap_or_else(|| tr("This setting is managed by your administrator"))
+	}
+}
+
+/// The defaults and locked keys that administrators set for every user of the system in
+/// `/etc/cosmic-settings/policy.toml`, such as:
+///
+/// ```toml
+/// [defaults]
+/// "cosmic-dock/size" = "small"
+///
+/// [locked."cosmic-comp/hot-corner"]
+/// value = false
+/// reason = "Hot corners are turned off on shared machines"
+/// ```
+///
+/// Defaults are used for keys that users haven't set, while locked keys always have their
+/// locked value.
+///
+/// A policy that exists but can't be read locks every key to its default, and a lock whose
+/// value is invalid locks its key to its default, so that mistakes never unlock anything.
+#[derive(Debug, Default, Deserialize)]
+pub struct Policy {
+	#[serde(default)]
+	defaults: BTreeMap<String, toml::Value>,
+	#[serde(default)]
+	locked: BTreeMap<String, LockDescription>,
+	/// Whether the policy couldn't be read, which locks every key.
+	#[serde(skip)]
+	invalid: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockDescription {
+	value: toml::Value,
+	reason: Option<String>,
+}
+
+/// Returns the path of the policy file.
+fn path() -> PathBuf {
+	#[cfg(debug_assertions)]
+	if let Some(path) = std::env::var_os(PATH_ENV) {
+		return PathBuf::from(path);
+	}
+	PathBuf::from(PATH)
+}
+
+impl Policy {
+	/// Reads the policy of the system. There is no policy if the file doesn't exist.
+	pub fn load() -> Self {
+		let path = path();
+		let text = match fs::read_to_string(&path) {
+			Ok(text) => text,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return Self::default(),
+			Err(err) => {
+				error!(%err, path = %path.display(), "Failed to read settings policy, locking every key");
+				return Self::invalid();
+			}
+		};
+		Self::parse(&text).unwrap_or_else(|err| {
+			error!(%err, path = %path.display(), "Invalid settings policy, locking every key");
+			Self::invalid()
+		})
+	}
+
+	fn parse(text: &str) -> Result<Self, toml::de::Error> {
+		toml::from_str(text)
+	}
+
+	/// Returns the policy used when the real one can't be read, which locks every key.
+	fn invalid() -> Self {
+		Self {
+			invalid: true,
+			..Self::default()
+		}
+	}
+
+	/// Returns the value a key has when the user hasn't set it.
+	pub fn default_value(&self, key: &Key) -> Value {
+		let value = match self.defaults.get(&key.to_string()) {
+			Some(value) => value,
+			None => return key.default_value(),
+		};
+		key.parse_toml(value).unwrap_or_else(|err| {
+			warn!(%err, "Ignoring invalid default in settings policy");
+			key.default_value()
+		})
+	}
+
+	/// Returns the lock on a key, if an administrator has locked it.
+	pub fn lock(&self, key: &Key) -> Option<Lock> {
+		if self.invalid {
+			return Some(Lock {
+				value: key.default_value(),
+				reason: None,
+			});
+		}
+		let lock = self.locked.get(&key.to_string())?;
+		let value = key.parse_toml(&lock.value).unwrap_or_else(|err| {
+			error!(%err, "Invalid lock in settings policy, locking to the default");
+			self.default_value(key)
+		});
+		Some(Lock {
+			value,
+			reason: lock.reason.clone(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::KeyKind;
+
+	const HOT_CORNER: Key = Key {
+		component: "cosmic-comp",
+		name: "hot-corner",
+		kind: KeyKind::Bool { default: true },
+	};
+	const SIZE: Key = Key {
+		component: "cosmic-dock",
+		name: "size",
+		kind: KeyKind::Choice {
+			default: "medium",
+			choices: &["small", "medium", "large"],
+		},
+	};
+
+	fn policy(text: &str) -> Policy {
+		Policy::parse(text).unwrap()
+	}
+
+	#[test]
+	fn defaults() {
+		let policy = policy(
+			r#"
+			[defaults]
+			"cosmic-dock/size" = "small"
+			"#,
+		);
+		assert_eq!(
+			policy.default_value(&SIZE),
+			Value::Choice("small".to_string())
+		);
+		assert_eq!(policy.default_value(&HOT_CORNER), Value::Bool(true));
+		assert!(policy.lock(&SIZE).is_none());
+	}
+
+	#[test]
+	fn invalid_default_falls_back() {
+		let policy = policy(
+			r#"
+			[defaults]
+			"cosmic-dock/size" = "huge"
+			"#,
+		);
+		assert_eq!(policy.default_value(&SIZE), SIZE.default_value());
+	}
+
+	#[test]
+	fn locked() {
+		let policy = policy(
+			r#"
+			[locked."cosmic-comp/hot-corner"]
+			value = false
+			reason = "Hot corners are turned off on shared machines"
+
+			[locked."cosmic-dock/size"]
+			value = "large"
+			"#,
+		);
+		let lock = policy.lock(&HOT_CORNER).unwrap();
+		assert_eq!(lock.value, Value::Bool(false));
+		assert_eq!(
+			lock.describe(),
+			"Hot corners are turned off on shared machines"
+		);
+		let lock = policy.lock(&SIZE).unwrap();
+		assert_eq!(lock.value, Value::Choice("large".to_string()));
+		assert_eq!(lock.reason, None);
+	}
+
+	#[test]
+	fn invalid_lock_keeps_key_locked() {
+		let policy = policy(
+			r#"
+			[defaults]
+			"cosmic-dock/size" = "small"
+
+			[locked."cosmic-comp/hot-corner"]
+			value = "sometimes"
+
+			[locked."cosmic-dock/size"]
+			value = "huge"
+			"#,
+		);
+		assert_eq!(policy.lock(&HOT_CORNER).unwrap().value, Value::Bool(true));
+		assert_eq!(
+			policy.lock(&SIZE).unwrap().value,
+			Value::Choice("small".to_string())
+		);
+	}
+
+	#[test]
+	fn invalid_policy_locks_every_key() {
+		assert!(Policy::parse("[locked.\"cosmic-comp/hot-corner\"]").is_err());
+		let policy = Policy::invalid();
+		assert_eq!(policy.lock(&HOT_CORNER).unwrap().value, Value::Bool(true));
+		assert_eq!(policy.lock(&SIZE).unwrap().value, SIZE.default_value());
+	}
+
+	#[test]
+	fn empty() {
+		let policy = policy("");
+		assert_eq!(policy.default_value(&SIZE), SIZE.default_value());
+		assert!(policy.lock(&HOT_CORNER).is_none());
+	}
+}
@@ -0,0 +1,448 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{Config, Key, Source, Value};
+use anyhow::{bail, Context, Result};
+use gtk4::glib;
+use serde::{Deserialize, Serialize};
+use std::{
+	collections::BTreeMap,
+	fs, io,
+	path::{Path, PathBuf},
+};
+
+/// A named set of values, such as the dock hidden and hot corners off for presenting, which
+/// are applied over the current settings while the profile is active.
+#[derive(Debug, Clone)]
+pub struct Profile {
+	pub name: String,
+	pub values: Vec<(&'static Key, Value)>,
+}
+
+/// The profile that is active, along with the values its keys had before it was activated.
+#[derive(Debug, Serialize, Deserialize)]
+struct Active {
+	name: String,
+	#[serde(default)]
+	previous: BTreeMap<String, toml::Value>,
+}
+
+/// The settings profiles, which are stored as tables of values by key in
+/// `$XDG_CONFIG_HOME/cosmic-settings/profiles.toml`, such as:
+///
+/// ```toml
+/// [Presentation]
+/// "cosmic-dock/visibility" = "always-hide"
+/// "cosmic-comp/hot-corner" = false
+/// ```
+///
+/// The active profile is remembered in `$XDG_DATA_HOME/cosmic-settings/active-profile`,
+/// along with the values to restore once it's deactivated.
+pub struct Profiles {
+	path: PathBuf,
+	active_path: PathBuf,
+	/// Every key this app knows about, which profiles may set.
+	keys: Vec<&'static Key>,
+}
+
+impl Profiles {
+	pub fn new(keys: Vec<&'static Key>) -> Self {
+		Self {
+			path: glib::user_config_dir()
+				.join("cosmic-settings")
+				.join("profiles.toml"),
+			active_path: glib::user_data_dir()
+				.join("cosmic-settings")
+				.join("active-profile"),
+			keys,
+		}
+	}
+
+	/// Returns every key that profiles may set.
+	pub fn keys(&self) -> &[&'static Key] {
+		&self.keys
+	}
+
+	/// Looks up a key by its `component/name` identifier.
+	pub fn key(&self, id: &str) -> Option<&'static Key> {
+		self.keys.iter().find(|key| key.to_string() == id).copied()
+	}
+
+	/// Returns every profile, sorted by name.
+	pub fn list(&self) -> Result<Vec<Profile>> {
+		let profiles = self.read()?;
+		Ok(profiles
+			.into_iter()
+			.map(|(name, values)| Profile {
+				values: self.resolve(&name, &values),
+				name,
+			})
+			.collect())
+	}
+
+	pub fn get(&self, name: &str) -> Result<Profile> {
+		self.list()?
+			.into_iter()
+			.find(|profile| profile.name == name)
+			.with_context(|| format!("no profile named '{}'", name))
+	}
+
+	/// Returns the name of the active profile, if there is one.
+	pub fn active(&self) -> Option<String> {
+		self.read_active().map(|active| active.name)
+	}
+
+	/// Stores a profile, replacing the one with the same name. If that profile is active, its
+	/// new values are applied.
+	pub fn save(&self, config: &Config, profile: &Profile) -> Result<()> {
+		if profile.name.trim().is_empty() {
+			bail!("profiles need a name");
+		}
+		let mut profiles = self.read()?;
+		let values = profile
+			.values
+			.iter()
+			.map(|(key, value)| (key.to_string(), value.to_toml()))
+			.collect();
+		profiles.insert(profile.name.clone(), values);
+		self.write(&profiles)?;
+		if self.active().as_deref() == Some(profile.name.as_str()) {
+			self.activate(config, profile)?;
+		}
+		Ok(())
+	}
+
+	/// Deletes a profile, deactivating it first if it's active.
+	pub fn remove(&self, config: &Config, name: &str) -> Result<()> {
+		let mut profiles = self.read()?;
+		if profiles.remove(name).is_none() {
+			bail!("no profile named '{}'", name);
+		}
+		if self.active().as_deref() == Some(name) {
+			self.deactivate(config)?;
+		}
+		self.write(&profiles)
+	}
+
+	/// Applies the values of a profile, deactivating the active profile first.
+	///
+	/// If any value can't be applied, those that were are restored and the profile that was
+	/// active is activated again, so that either every value of the profile is applied or
+	/// nothing changes.
+	pub fn activate(&self, config: &Config, profile: &Profile) -> Result<()> {
+		let active = self.read_active();
+		self.deactivate(config)?;
+		if let Err(err) = self.apply_profile(config, profile) {
+			if let Some(active) = active {
+				self.reactivate(config, active);
+			}
+			return Err(err)
+				.with_context(|| format!("failed to activate profile '{}'", profile.name));
+		}
+		Ok(())
+	}
+
+	/// Restores the values that the keys of the active profile had before it was activated.
+	///
+	/// Does nothing if no profile is active.
+	pub fn deactivate(&self, config: &Config) -> Result<()> {
+		let active = match self.read_active() {
+			Some(active) => active,
+			None => return Ok(()),
+		};
+		let previous = self.resolve(&active.name, &active.previous);
+		apply(config, &previous)
+			.with_context(|| format!("failed to deactivate profile '{}'", active.name))?;
+		self.clear_active()
+	}
+
+	/// Applies the values of a profile while no profile is active, restoring those that were
+	/// applied if any fails.
+	fn apply_profile(&self, config: &Config, profile: &Profile) -> Result<()> {
+		let previous = profile
+			.values
+			.iter()
+			.map(|(key, _)| (*key, config.get(key)))
+			.collect::<Vec<_>>();
+		// Remembered before anything changes, so that the previous values can still be
+		// restored if applying the profile is interrupted.
+		self.write_active(&Active {
+			name: profile.name.clone(),
+			previous: previous
+				.iter()
+				.map(|(key, value)| (key.to_string(), value.to_toml()))
+				.collect(),
+		})?;
+		if let Err(err) = apply(config, &profile.values) {
+			if let Err(err) = apply(config, &previous) {
+				error!(?err, profile = %profile.name, "Failed to restore settings");
+			}
+			self.clear_active()?;
+			return Err(err);
+		}
+		Ok(())
+	}
+
+	/// Activates a profile again after it was deactivated, keeping the values from before it
+	/// was first activated to restore once it's deactivated.
+	fn reactivate(&self, config: &Config, active: Active) {
+		let result = self.get(&active.name).and_then(|profile| {
+			self.write_active(&active)?;
+			apply(config, &profile.values)
+		});
+		if let Err(err) = result {
+			error!(?err, profile = %active.name, "Failed to reactivate profile");
+		}
+	}
+
+	/// Looks up the keys of stored values, leaving out those this app doesn't know about,
+	/// such as those of panels that aren't installed.
+	fn resolve(
+		&self,
+		name: &str,
+		values: &BTreeMap<String, toml::Value>,
+	) -> Vec<(&'static Key, Value)> {
+		values
+			.iter()
+			.filter_map(|(id, value)| {
+				let key = match self.key(id) {
+					Some(key) => key,
+					None => {
+						warn!(profile = %name, key = %id, "Ignoring unknown key in profile");
+						return None;
+					}
+				};
+				match key.parse_toml(value) {
+					Ok(value) => Some((key, value)),
+					Err(err) => {
+						warn!(%err, profile = %name, "Ignoring invalid value in profile");
+						None
+					}
+				}
+			})
+			.collect()
+	}
+
+	fn read(&self) -> Result<BTreeMap<String, BTreeMap<String, toml::Value>>> {
+		let text = match fs::read_to_string(&self.path) {
+			Ok(text) => text,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+			Err(err) => {
+				return Err(err)
+					.with_context(|| format!("failed to read '{}'", self.path.display()))
+			}
+		};
+		toml::from_str(&text)
+			.with_context(|| format!("invalid profiles in '{}'", self.path.display()))
+	}
+
+	fn write(&self, profiles: &BTreeMap<String, BTreeMap<String, toml::Value>>) -> Result<()> {
+		let text = toml::to_string(profiles).context("failed to serialize profiles")?;
+		write(&self.path, &text)
+	}
+
+	fn read_active(&self) -> Option<Active> {
+		let text = match fs::read_to_string(&self.active_path) {
+			Ok(text) => text,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+			Err(err) => {
+				error!(%err, "Failed to read active profile");
+				return None;
+			}
+		};
+		match toml::from_str(&text) {
+			Ok(active) => Some(active),
+			Err(err) => {
+				error!(%err, "Ignoring invalid active profile");
+				None
+			}
+		}
+	}
+
+	fn write_active(&self, active: &Active) -> Result<()> {
+		let text = toml::to_string(active).context("failed to serialize active profile")?;
+		write(&self.active_path, &text)
+	}
+
+	fn clear_active(&self) -> Result<()> {
+		match fs::remove_file(&self.active_path) {
+			Ok(()) => Ok(()),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+			Err(err) => Err(err)
+				.with_context(|| format!("failed to remove '{}'", self.active_path.display())),
+		}
+	}
+}
+
+/// Sets every value, stopping at the first that fails. Keys that an administrator has locked
+/// are left alone.
+fn apply(config: &Config, values: &[(&'static Key, Value)]) -> Result<()> {
+	for (key, value) in values {
+		if config.lock(key).is_some() {
+			continue;
+		}
+		config.set(key, value.clone(), Source::Profile)?;
+	}
+	Ok(())
+}
+
+/// Replaces a file through a temporary one, so that it's never left partially written.
+fn write(path: &Path, text: &str) -> Result<()> {
+	let dir = path.parent().expect("profiles path has no parent");
+	fs::create_dir_all(dir).with_context(|| format!("failed to create '{}'", dir.display()))?;
+	let tmp = path.with_extension("tmp");
+	fs::write(&tmp, text).with_context(|| format!("failed to write '{}'", tmp.display()))?;
+	fs::rename(&tmp, path).with_context(|| format!("failed to replace '{}'", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::{History, KeyKind};
+
+	static SIZE: Key = Key {
+		component: "cosmic-dock",
+		name: "size",
+		kind: KeyKind::Choice {
+			default: "medium",
+			choices: &["small", "medium", "large"],
+		},
+	};
+	static AUTOHIDE: Key = Key {
+		component: "cosmic-dock",
+		name: "autohide",
+		kind: KeyKind::Bool { default: false },
+	};
+	/// Sorted after the dock keys, so that it's applied last.
+	static WRAP: Key = Key {
+		component: "cosmic-workspaces",
+		name: "wrap",
+		kind: KeyKind::Bool { default: false },
+	};
+
+	/// A config and profiles stored in their own temporary directory, with the dock size set
+	/// to large.
+	struct TestProfiles {
+		root: PathBuf,
+		config: Config,
+		profiles: Profiles,
+	}
+
+	impl TestProfiles {
+		fn new(name: &str, policy: &str) -> Self {
+			let root = std::env::temp_dir().join(format!(
+				"cosmic-settings-profiles-{}-{}",
+				name,
+				std::process::id()
+			));
+			let mut config = Config::with_root(root.join("config"));
+			config.policy = toml::from_str(policy).unwrap();
+			config.history = History::with_path(root.join("history"));
+			fs::create_dir_all(root.join("config").join("cosmic-dock")).unwrap();
+			fs::write(
+				root.join("config").join("cosmic-dock").join("size"),
+				"large",
+			)
+			.unwrap();
+			let profiles = Profiles {
+				path: root.join("profiles.toml"),
+				active_path: root.join("active-profile"),
+				keys: vec![&SIZE, &AUTOHIDE, &WRAP],
+			};
+			Self {
+				root,
+				config,
+				profiles,
+			}
+		}
+
+		fn save(&self, name: &str, values: Vec<(&'static Key, Value)>) -> Profile {
+			let profile = Profile {
+				name: name.to_owned(),
+				values,
+			};
+			self.profiles.save(&self.config, &profile).unwrap();
+			profile
+		}
+	}
+
+	impl Drop for TestProfiles {
+		fn drop(&mut self) {
+			let _ = fs::remove_dir_all(&self.root);
+		}
+	}
+
+	fn small() -> Value {
+		Value::Choice("small".to_owned())
+	}
+
+	fn large() -> Value {
+		Value::Choice("large".to_owned())
+	}
+
+	#[test]
+	fn activate_and_deactivate() {
+		let test = TestProfiles::new("activate", "");
+		let presentation = test.save(
+			"Presentation",
+			vec![(&SIZE, small()), (&AUTOHIDE, Value::Bool(true))],
+		);
+		let travel = test.save("Travel", vec![(&AUTOHIDE, Value::Bool(false))]);
+
+		test.profiles.activate(&test.config, &presentation).unwrap();
+		assert_eq!(test.profiles.active().as_deref(), Some("Presentation"));
+		assert_eq!(test.config.get(&SIZE), small());
+		assert_eq!(test.config.get(&AUTOHIDE), Value::Bool(true));
+
+		// Switching profiles restores the values from before the first one was activated.
+		test.profiles.activate(&test.config, &travel).unwrap();
+		assert_eq!(test.profiles.active().as_deref(), Some("Travel"));
+		assert_eq!(test.config.get(&SIZE), large());
+		assert_eq!(test.config.get(&AUTOHIDE), Value::Bool(false));
+
+		test.profiles.deactivate(&test.config).unwrap();
+		assert_eq!(test.profiles.active(), None);
+		assert_eq!(test.config.get(&SIZE), large());
+		assert_eq!(test.config.get(&AUTOHIDE), Value::Bool(false));
+	}
+
+	#[test]
+	fn failed_activation_rolls_back() {
+		let test = TestProfiles::new("rollback", "");
+		let desk = test.save("Desk", vec![(&AUTOHIDE, Value::Bool(true))]);
+		let travel = test.save("Travel", vec![(&SIZE, small()), (&WRAP, Value::Bool(true))]);
+		test.profiles.activate(&test.config, &desk).unwrap();
+
+		// A file where the workspaces directory should be makes setting the last value fail.
+		fs::write(test.root.join("config").join("cosmic-workspaces"), "").unwrap();
+		assert!(test.profiles.activate(&test.config, &travel).is_err());
+		assert_eq!(test.profiles.active().as_deref(), Some("Desk"));
+		assert_eq!(test.config.get(&SIZE), large());
+		assert_eq!(test.config.get(&AUTOHIDE), Value::Bool(true));
+
+		test.profiles.deactivate(&test.config).unwrap();
+		assert_eq!(test.config.get(&AUTOHIDE), Value::Bool(false));
+	}
+
+	#[test]
+	fn locked_keys_are_skipped() {
+		let test = TestProfiles::new(
+			"locked",
+			r#"
+			[locked."cosmic-dock/size"]
+			value = "small"
+			"#,
+		);
+		let presentation = test.save(
+			"Presentation",
+			vec![(&SIZE, large()), (&AUTOHIDE, Value::Bool(true))],
+		);
+
+		test.profiles.activate(&test.config, &presentation).unwrap();
+		assert_eq!(test.config.get(&SIZE), small());
+		assert_eq!(test.config.get(&AUTOHIDE), Value::Bool(true));
+
+		test.profiles.deactivate(&test.config).unwrap();
+		assert_eq!(test.config.get(&SIZE), small());
+		assert_eq!(test.config.get(&AUTOHIDE), Value::Bool(false));
+	}
+}
@@ -0,0 +1,50 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::{
+	collections::HashSet,
+	path::{Path, PathBuf},
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Watches the config directories of components, and reports the path of every file
+/// that is changed inside of them.
+pub struct Watcher {
+	inner: RecommendedWatcher,
+	dirs: HashSet<PathBuf>,
+}
+
+impl Watcher {
+	pub fn new(tx: UnboundedSender<PathBuf>) -> notify::Result<Self> {
+		let inner =
+			notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+				Ok(event) => {
+					for path in event.paths {
+						let _ = tx.send(path);
+					}
+				}
+				Err(err) => error!(%err, "Failed to watch config directory"),
+			})?;
+		Ok(Self {
+			inner,
+			dirs: HashSet::new(),
+		})
+	}
+
+	/// Starts watching a directory, creating it if it doesn't exist yet.
+	pub fn watch(&mut self, dir: &Path) {
+		if self.dirs.contains(dir) {
+			return;
+		}
+		if let Err(err) = std::fs::create_dir_all(dir) {
+			error!(%err, dir = %dir.display(), "Failed to create config directory");
+			return;
+		}
+		match self.inner.watch(dir, RecursiveMode::NonRecursive) {
+			Ok(()) => {
+				self.dirs.insert(dir.to_path_buf());
+			}
+			Err(err) => error!(%err, dir = %dir.display(), "Failed to watch config directory"),
+		}
+	}
+}
@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The session D-Bus interface that other components use to drive the settings window,
+//! instead of spawning new processes.
+
+use crate::ui::SettingsGui;
+use gtk4::{
+	glib::{self, clone},
+	prelude::*,
+};
+use std::rc::Rc;
+use tokio::sync::{
+	mpsc::{self, UnboundedReceiver, UnboundedSender},
+	oneshot,
+};
+use zbus::{dbus_interface, fdo, Connection, SignalContext};
+
+pub const NAME: &str = "com.system76.CosmicSettings";
+pub const PATH: &str = "/com/system76/CosmicSettings";
+
+/// A request from a D-Bus client, which is handled on the GTK main loop.
+#[derive(Debug)]
+pub enum Request {
+	ShowPage(String, oneshot::Sender<bool>),
+	Search(String),
+	ListPages(oneshot::Sender<Vec<String>>),
+	GetVisiblePage(oneshot::Sender<String>),
+}
+
+pub struct Interface {
+	requests: UnboundedSender<Request>,
+}
+
+impl Interface {
+	fn send(&self, request: Request) -> fdo::Result<()> {
+		self.requests
+			.send(request)
+			.map_err(|_| fdo::Error::Failed("the settings window has closed".into()))
+	}
+
+	async fn reply<T>(
+		&self,
+		request: impl FnOnce(oneshot::Sender<T>) -> Request,
+	) -> fdo::Result<T> {
+		let (tx, rx) = oneshot::channel();
+		self.send(request(tx))?;
+		rx.await
+			.map_err(|_| fdo::Error::Failed("the settings window has closed".into()))
+	}
+}
+
+#[dbus_interface(name = "com.system76.CosmicSettings")]
+impl Interface {
+	/// Shows the page at the given path, such as `wifi` or `desktop/dock`.
+	async fn show_page(&self, path: String) -> fdo::Result<()> {
+		if self.reply(|tx| Request::ShowPage(path.clone(), tx)).await? {
+			Ok(())
+		} else {
+			Err(fdo::Error::InvalidArgs(format!("no page at '{}'", path)))
+		}
+	}
+
+	/// Opens the search bar and searches for the given text.
+	async fn search(&self, query: String) -> fdo::Result<()> {
+		self.send(Request::Search(query))
+	}
+
+	/// Returns the path of every page.
+	async fn list_pages(&self) -> fdo::Result<Vec<String>> {
+		self.reply(Request::ListPages).await
+	}
+
+	/// Returns the path of the page that is currently shown, or an empty string if search
+	/// results are being shown instead.
+	async fn get_visible_page(&self) -> fdo::Result<String> {
+		self.reply(Request::GetVisiblePage).await
+	}
+
+	/// Emitted when another page is shown.
+	#[dbus_interface(signal)]
+	async fn page_changed(ctxt: &SignalContext<'_>, path: &str) -> zbus::Result<()>;
+}
+
+/// Exports the interface on the given connection, and emits `PageChanged` for every path
+/// received from `pages` until the sender is dropped.
+///
+/// This takes any connection so that it can be served on a private bus.
+pub async fn serve(
+	connection: &Connection,
+	requests: UnboundedSender<Request>,
+	mut pages: UnboundedReceiver<String>,
+) -> zbus::Result<()> {
+	connection
+		.object_server()
+		.at(PATH, Interface { requests })
+		.await?;
+	connection.request_name(NAME).await?;
+	let ctxt = SignalContext::new(connection, PATH)?;
+	while let Some(path) = pages.recv().await {
+		Interface::page_changed(&ctxt, &path).await?;
+	}
+	Ok(())
+}
+
+/// Serves the interface on the session bus, and handles its requests with the given UI.
+pub fn setup(ui: Rc<SettingsGui>) {
+	let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+	let (page_tx, page_rx) = mpsc::unbounded_channel();
+	crate::task::spawn(async move {
+		let connection = match Connection::session().await {
+			Ok(connection) => connection,
+			Err(err) => {
+				error!(%err, "Failed to connect to session dbus");
+				return;
+			}
+		};
+		if let Err(err) = serve(&connection, request_tx, page_rx).await {
+			error!(%err, "Failed to serve settings dbus interface");
+		}
+	});
+
+	ui.content
+		.connect_visible_child_name_notify(clone!(@weak ui => move |_| {
+			if let Some(path) = ui.visible_page() {
+				let _ = page_tx.send(path);
+			}
+		}));
+
+	crate::task::spawn_local(async move {
+		while let Some(request) = request_rx.recv().await {
+			match request {
+				Request::ShowPage(path, reply) => {
+					let _ = reply.send(ui.nav.show_page(&path));
+					present(&ui);
+				}
+				Request::Search(query) => {
+					ui.search.bar.set_text(&query);
+					present(&ui);
+				}
+				Request::ListPages(reply) => {
+					let _ = reply.send(ui.page_paths());
+				}
+				Request::GetVisiblePage(reply) => {
+					let _ = reply.send(ui.visible_page().unwrap_or_default());
+				}
+			}
+		}
+	});
+}
+
+fn present(ui: &SettingsGui) {
+	if let Some(window) = ui
+		.content
+		.root()
+		.and_then(|root| root.downcast::<gtk4::Window>().ok())
+	{
+		window.present();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+	use std::{
+		io::{BufRead, BufReader},
+		process::{Child, Command, Stdio},
+		time::Duration,
+	};
+	use zbus::{ConnectionBuilder, Proxy};
+
+	/// A private session bus, which is stopped when dropped.
+	struct Bus {
+		daemon: Child,
+		address: String,
+	}
+
+	impl Bus {
+		fn start() -> Self {
+			let mut daemon = Command::new("dbus-daemon")
+				.args(["--session", "--nofork", "--print-address"])
+				.stdout(Stdio::piped())
+				.spawn()
+				.expect("failed to start dbus-daemon");
+			let mut address = String::new();
+			BufReader::new(daemon.stdout.take().unwrap())
+				.read_line(&mut address)
+				.expect("failed to read bus address");
+			Self {
+				daemon,
+				address: address.trim().to_string(),
+			}
+		}
+
+		async fn connect(&self) -> Connection {
+			ConnectionBuilder::address(self.address.as_str())
+				.unwrap()
+				.build()
+				.await
+				.unwrap()
+		}
+	}
+
+	impl Drop for Bus {
+		fn drop(&mut self) {
+			let _ = self.daemon.kill();
+			let _ = self.daemon.wait();
+		}
+	}
+
+	#[tokio::test]
+	async fn interface() {
+		let bus = Bus::start();
+		let server = bus.connect().await;
+		let client = bus.connect().await;
+
+		let (request_tx, mut request_rx) = mpsc::unbounded_channel();
+		let (page_tx, page_rx) = mpsc::unbounded_channel();
+		tokio::spawn(async move { serve(&server, request_tx, page_rx).await });
+
+		// Stand in for the settings window, which has a Wi-Fi page and a dock page.
+		let (search_tx, mut search_rx) = mpsc::unbounded_channel();
+		tokio::spawn(async move {
+			let pages = ["wifi", "desktop/dock"];
+			let mut visible = String::from("wifi");
+			while let Some(request) = request_rx.recv().await {
+				match request {
+					Request::ShowPage(path, reply) => {
+						let found = pages.contains(&path.as_str());
+						if found {
+							visible = path;
+							let _ = page_tx.send(visible.clone());
+						}
+						let _ = reply.send(found);
+					}
+					Request::Search(query) => {
+						visible.clear();
+						let _ = search_tx.send(query);
+					}
+					Request::ListPages(reply) => {
+						let _ = reply.send(pages.iter().map(|page| page.to_string()).collect());
+					}
+					Request::GetVisiblePage(reply) => {
+						let _ = reply.send(visible.clone());
+					}
+				}
+			}
+		});
+
+		let dbus = fdo::DBusProxy::new(&client).await.unwrap();
+		tokio::time::timeout(Duration::from_secs(5), async {
+			while !dbus.name_has_owner(NAME.try_into().unwrap()).await.unwrap() {
+				tokio::time::sleep(Duration::from_millis(10)).await;
+			}
+		})
+		.await
+		.expect("the interface was never served");
+
+		let proxy = Proxy::new(&client, NAME, PATH, NAME).await.unwrap();
+		let mut page_changed = proxy.receive_signal("PageChanged").await.unwrap();
+
+		let pages: Vec<String> = proxy.call("ListPages", &()).await.unwrap();
+		assert_eq!(pages, ["wifi", "desktop/dock"]);
+		let visible: String = proxy.call("GetVisiblePage", &()).await.unwrap();
+		assert_eq!(visible, "wifi");
+
+		let () = proxy.call("ShowPage", &("desktop/dock",)).await.unwrap();
+		let signal = tokio::time::timeout(Duration::from_secs(5), page_changed.next())
+			.await
+			.expect("PageChanged was never emitted")
+			.unwrap();
+		assert_eq!(signal.body::<String>().unwrap(), "desktop/dock");
+		let visible: String = proxy.call("GetVisiblePage", &()).await.unwrap();
+		assert_eq!(visible, "desktop/dock");
+
+		match proxy.call::<_, _, ()>("ShowPage", &("nowhere",)).await {
+			Err(zbus::Error::MethodError(name, ..)) => {
+				assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.InvalidArgs");
+			}
+			result => panic!("expected InvalidArgs, got {:?}", result),
+		}
+
+		let () = proxy.call("Search", &("dock",)).await.unwrap();
+		assert_eq!(search_rx.recv().await.as_deref(), Some("dock"));
+		let visible: String = proxy.call("GetVisiblePage", &()).await.unwrap();
+		assert_eq!(visible, "");
+	}
+}
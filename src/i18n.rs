@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Translation of the text shown to the user, using the gettext catalogs installed for the
+//! user's locale from `po/`.
+//!
+//! Messages are marked with [`tr`] where they're shown, or with [`mark`] where they have to
+//! stay in English, and `just pot` extracts everything marked into `po/cosmic-settings.pot`.
+
+use gettextrs::{
+	bind_textdomain_codeset, bindtextdomain, gettext, ngettext, setlocale, textdomain,
+	LocaleCategory,
+};
+
+const DOMAIN: &str = "cosmic-settings";
+
+/// Loads the catalog of the user's locale. Messages stay in English if there is none.
+pub fn init() {
+	setlocale(LocaleCategory::LcAll, "");
+	// Set by `just` to the locale directory under the prefix the catalogs are installed to.
+	let locale_dir = option_env!("LOCALEDIR").unwrap_or("/usr/share/locale");
+	let bound = bindtextdomain(DOMAIN, locale_dir)
+		.and_then(|_| bind_textdomain_codeset(DOMAIN, "UTF-8"))
+		.and_then(|_| textdomain(DOMAIN));
+	if let Err(err) = bound {
+		warn!(%err, "Failed to load translations");
+	}
+}
+
+/// Translates a message into the user's language.
+pub fn tr(msgid: &str) -> String {
+	if msgid.is_empty() {
+		// The empty message translates to the header of the catalog.
+		return String::new();
+	}
+	gettext(msgid)
+}
+
+/// Translates a message that depends on a number, such as `"{count} results"`, choosing the
+/// plural form that the user's language uses for `n`.
+pub fn tr_n(singular: &str, plural: &str, n: usize) -> String {
+	ngettext(singular, plural, n as u32)
+}
+
+/// Marks a message for translation without translating it, for names that have to stay in
+/// English, such as those that page paths are made from. They're translated with [`tr`] where
+/// they're shown.
+pub const fn mark(msgid: &'static str) -> &'static str {
+	msgid
+}
+
+/// Returns the search keywords in a `;`-separated list, such as `"wifi;wireless"`, followed by
+/// those of its translation that differ, so that search works in either language.
+///
+/// Translators don't need to translate every keyword, and may add their own.
+pub fn keywords(list: &str) -> Vec<String> {
+	let mut keywords = Vec::new();
+	for keyword in list.split(';').chain(tr(list).split(';')) {
+		let keyword = keyword.trim().to_lowercase();
+		if !keyword.is_empty() && !keywords.contains(&keyword) {
+			keywords.push(keyword);
+		}
+	}
+	keywords
+}
@@ -7,17 +7,26 @@ extern crate relm4_macros;
 #[macro_use]
 extern crate tracing;
 
+mod a11y;
+mod cli;
+mod config;
+mod dbus;
+mod i18n;
+mod panels;
 mod sections;
 mod task;
+mod theme;
 mod ui;
 mod widgets;
 
-use crate::{sections::SettingsGroupStore, ui::section};
+use crate::{i18n::tr, sections::SettingsGroupStore, ui::section};
 use gtk4::{
-	gdk::Display, gio::ApplicationFlags, prelude::*, CssProvider, StyleContext,
-	STYLE_PROVIDER_PRIORITY_APPLICATION,
+	gdk::Display,
+	gio::ApplicationFlags,
+	glib::{self, clone},
+	prelude::*,
 };
-use once_cell::sync::OnceCell;
+use once_cell::{sync::OnceCell, unsync};
 use std::{cell::RefCell, rc::Rc};
 use tokio::runtime::{self, Runtime};
 
@@ -25,11 +34,50 @@ static RT: OnceCell<runtime::Runtime> = OnceCell::new();
 
 fn main() {
 	setup_logging();
+	i18n::init();
+	let args = std::env::args().skip(1).collect::<Vec<_>>();
+	match cli::Command::parse(&args) {
+		Ok(Some(command)) => {
+			if let Err(err) = command.run() {
+				eprintln!("error: {:#}", err);
+				std::process::exit(1);
+			}
+			return;
+		}
+		Ok(None) => {}
+		Err(err) => {
+			eprintln!("error: {:#}", err);
+			std::process::exit(2);
+		}
+	}
 	let application = gtk4::Application::new(
 		Some("com.system76.cosmic.settings"),
-		ApplicationFlags::default(),
+		ApplicationFlags::HANDLES_COMMAND_LINE,
 	);
-	application.connect_activate(build_ui);
+	// The window is only built once, and later launches switch the existing window instead.
+	let ui = Rc::new(unsync::OnceCell::new());
+	application.connect_activate(clone!(@strong ui => move |application| {
+		ui.get_or_init(|| build_ui(application));
+		present(application);
+	}));
+	application.connect_command_line(move |application, command_line| {
+		let args = command_line
+			.arguments()
+			.into_iter()
+			.skip(1)
+			.map(|arg| arg.to_string_lossy().into_owned())
+			.collect::<Vec<_>>();
+		let launch = match cli::Launch::parse(&args) {
+			Ok(launch) => launch,
+			Err(err) => {
+				error!("{:#}", err);
+				return 2;
+			}
+		};
+		open(ui.get_or_init(|| build_ui(application)), launch);
+		present(application);
+		0
+	});
 	application.run();
 }
 
@@ -42,36 +90,64 @@ fn setup_logging() {
 	tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 }
 
-fn build_ui(application: &gtk4::Application) {
-	let provider = cascade! {
-		CssProvider::new();
-		..load_from_data(include_bytes!(concat!(env!("OUT_DIR"), "/style.css")));
-	};
-	// We give the CssProvided to the default screen so the CSS rules we added
-	// can be applied to our window.
-	StyleContext::add_provider_for_display(
-		&Display::default().expect("Could not connect to a display."),
-		&provider,
-		STYLE_PROVIDER_PRIORITY_APPLICATION,
-	);
+fn present(application: &gtk4::Application) {
+	if let Some(window) = application.active_window() {
+		window.present();
+	}
+}
+
+/// Switches the settings window to the page or search given on the command line.
+fn open(ui: &ui::SettingsGui, launch: cli::Launch) {
+	if let Some(page) = launch.page {
+		if !ui.nav.show_page(&page) {
+			warn!(%page, "No settings page at the given path");
+		}
+	}
+	if let Some(search) = launch.search {
+		ui.search.bar.set_text(&search);
+	}
+}
 
+fn build_ui(application: &gtk4::Application) -> Rc<ui::SettingsGui> {
 	RT.set(Runtime::new().unwrap()).unwrap();
+	panels::allow_helpers();
+	theme::Theme::setup(&Display::default().expect("Could not connect to a display."));
 	let window = gtk4::ApplicationWindow::builder()
 		.application(application)
-		.title("Settings")
+		.title(&tr("Settings"))
 		.default_width(800)
 		.default_height(600)
 		.build();
 
 	let sections_store: SettingsGroupStore = Rc::new(RefCell::new(Vec::new()));
 	let ui = Rc::new(ui::SettingsGui::new(&window));
+	ui.config.enforce_policy(&sections::all_keys());
 	section::setup::<sections::WifiSection>(ui.clone(), sections_store.clone());
 	section::setup::<sections::DesktopSection>(ui.clone(), sections_store.clone());
 	section::setup::<sections::KeyboardSection>(ui.clone(), sections_store.clone());
+	for panel in panels::load() {
+		panel.apply_changes(&ui.config);
+		section::add(
+			ui.clone(),
+			sections_store.clone(),
+			panel.name,
+			&panel.icon,
+			panel.layout.to_layout(),
+			sections::Availability::default(),
+		);
+	}
+	section::setup::<sections::ProfilesSection>(ui.clone(), sections_store.clone());
 	section::setup::<sections::AboutSection>(ui.clone(), sections_store.clone());
+	section::setup::<sections::HistorySection>(ui.clone(), sections_store.clone());
 	ui.search.setup(ui.clone(), sections_store);
+	ui.navigation.restore();
+	ui.config.watch();
+	dbus::setup(ui.clone());
+	ui::shortcuts::setup(application, &window, ui.clone());
+	ui::bundle::setup(&window, ui.clone());
 	ui.content
 		.add_named(&ui.search.all_results_scroll, Some("_search"));
 
 	window.show();
+	ui
 }
@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Settings panels installed by other packages.
+//!
+//! A panel is registered by dropping a manifest into `cosmic-settings/panels/` in any XDG data
+//! directory, such as `/usr/share/cosmic-settings/panels/acme-fans.toml`:
+//!
+//! ```toml
+//! name = "Fan Control"
+//! icon = "weather-windy-symbolic"
+//! # Either a page description, relative to this manifest...
+//! page = "acme-fans.page.toml"
+//! # ...or a helper program that prints one when run as `helper describe`.
+//! helper = "/usr/libexec/acme-fans-settings"
+//! ```
+//!
+//! Page descriptions use the format of [`SectionDescription`]. When a setting of a panel with
+//! a helper is changed in the app, the helper is run as `helper apply <key> <value>`.
+//! Helpers are only run by the settings window, so panels with a helper are left out of the
+//! command line, and a helper that takes longer than [`DESCRIBE_TIMEOUT`] is killed.
+//!
+//! Manifests in `$XDG_DATA_HOME` replace system manifests of the same file name. Panels named
+//! like a built-in section or another panel are skipped. When the app runs with elevated
+//! privileges, only manifests in [`SYSTEM_DATA_DIRS`] are read.
+
+use crate::{
+	config::Config,
+	sections::{self, DeclarativeLayout, SectionDescription},
+	ui::nav::path_segment,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use gtk4::glib;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::{
+	collections::HashSet,
+	fs,
+	path::{Path, PathBuf},
+	rc::Rc,
+	sync::atomic::{AtomicBool, Ordering},
+	time::Duration,
+};
+
+/// How long a helper may take to describe its panel before it's killed.
+const DESCRIBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The data directories read when the app runs with elevated privileges, which ignores the
+/// user's data directory and `XDG_DATA_DIRS` since the user controls both.
+const SYSTEM_DATA_DIRS: [&str; 2] = ["/usr/local/share", "/usr/share"];
+
+/// Whether panels may be described by running their helpers.
+static HELPERS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+	name: String,
+	#[serde(default = "default_icon")]
+	icon: String,
+	page: Option<PathBuf>,
+	helper: Option<PathBuf>,
+}
+
+fn default_icon() -> String {
+	"preferences-other-symbolic".into()
+}
+
+impl Manifest {
+	fn load(path: &Path) -> Result<Self> {
+		let text = fs::read_to_string(path)?;
+		Ok(toml::from_str(&text)?)
+	}
+}
+
+/// Every installed panel, which are only loaded once since loading one may run its helper,
+/// and since their keys are never freed.
+static PANELS: Lazy<Vec<Panel>> = Lazy::new(find);
+
+/// A settings panel loaded from a manifest.
+#[derive(Clone)]
+pub struct Panel {
+	/// The name the panel appears as in the nav.
+	pub name: &'static str,
+	/// The icon that appears next to the panel in the nav.
+	pub icon: String,
+	/// The settings groups of the panel.
+	pub layout: DeclarativeLayout,
+	/// The program that applies changed settings, if the panel has one.
+	pub helper: Option<PathBuf>,
+}
+
+impl Panel {
+	fn new(manifest: Manifest, description: Result<String>) -> Result<Self> {
+		let layout = SectionDescription::parse(&description?)
+			.and_then(SectionDescription::build)
+			.context("invalid page description")?;
+		Ok(Self {
+			name: Box::leak(manifest.name.into_boxed_str()),
+			icon: manifest.icon,
+			layout,
+			helper: manifest.helper,
+		})
+	}
+
+	/// Runs the helper of this panel whenever one of its settings is changed.
+	pub fn apply_changes(&self, config: &Rc<Config>) {
+		let helper = match &self.helper {
+			Some(helper) => helper,
+			None => return,
+		};
+		for key in self.layout.keys() {
+			let helper = helper.clone();
+			config.subscribe(key, move |value| {
+				let (helper, value) = (helper.clone(), value.to_string());
+				crate::task::spawn(async move {
+					let status = tokio::process::Command::new(&helper)
+						.arg("apply")
+						.arg(key.to_string())
+						.arg(&value)
+						.status()
+						.await;
+					match status {
+						Ok(status) if status.success() => {}
+						Ok(status) => {
+							error!(%status, %key, "Panel helper failed to apply setting")
+						}
+						Err(err) => error!(%err, %key, "Failed to run panel helper"),
+					}
+				});
+			});
+		}
+	}
+}
+
+/// Returns every installed panel, sorted by name.
+pub fn load() -> Vec<Panel> {
+	PANELS.clone()
+}
+
+/// Lets panels be described by their helpers, which only the settings window may do. This
+/// must be called before panels are first loaded.
+pub fn allow_helpers() {
+	HELPERS.store(true, Ordering::Relaxed);
+}
+
+/// Loads every installed panel, sorted by name. Panels that fail to load are logged and skipped.
+fn find() -> Vec<Panel> {
+	let helpers = HELPERS.load(Ordering::Relaxed);
+	let mut loaded = Vec::new();
+	let mut described = Vec::new();
+	for path in manifests() {
+		let manifest = match Manifest::load(&path) {
+			Ok(manifest) => manifest,
+			Err(err) => {
+				error!(?err, manifest = %path.display(), "Failed to load settings panel");
+				continue;
+			}
+		};
+		match (&manifest.page, &manifest.helper) {
+			(Some(page), None) => {
+				let page = path
+					.parent()
+					.expect("manifest path has no parent")
+					.join(page);
+				let description = fs::read_to_string(&page)
+					.with_context(|| format!("failed to read '{}'", page.display()));
+				loaded.push((path, Panel::new(manifest, description)));
+			}
+			// Helpers all run at once on the runtime, so that they can't hold up the window
+			// for longer than a single timeout between them.
+			(None, Some(helper)) if helpers => {
+				let task = crate::task::spawn(describe(helper.clone()));
+				described.push((path, manifest, task));
+			}
+			(None, Some(_)) => {
+				debug!(manifest = %path.display(), "Not running settings panel helper");
+			}
+			_ => {
+				let err = anyhow!("a panel must have either a 'page' or a 'helper'");
+				loaded.push((path, Err(err)));
+			}
+		}
+	}
+	for (path, manifest, task) in described {
+		let runtime = crate::RT
+			.get()
+			.expect("panel helpers were allowed before the runtime started");
+		let description = match runtime.block_on(task) {
+			Ok(description) => description,
+			Err(err) => Err(err.into()),
+		};
+		loaded.push((path, Panel::new(manifest, description)));
+	}
+
+	let mut panels = Vec::new();
+	for (path, panel) in loaded {
+		match panel {
+			Ok(panel) => panels.push(panel),
+			Err(err) => error!(?err, manifest = %path.display(), "Failed to load settings panel"),
+		}
+	}
+	panels.sort_by(|a, b| a.name.cmp(b.name));
+	// Pages are found by their path, so a panel can't share the name of another section.
+	let mut paths = sections::BUILT_IN
+		.iter()
+		.map(|name| path_segment(name))
+		.collect::<HashSet<_>>();
+	panels.retain(|panel| {
+		let unique = paths.insert(path_segment(panel.name));
+		if !unique {
+			error!(
+				name = panel.name,
+				"Skipping settings panel named like another section"
+			);
+		}
+		unique
+	});
+	panels
+}
+
+/// Returns the path of every manifest, where those in directories earlier in the list replace
+/// those of the same file name in later ones.
+fn manifests() -> Vec<PathBuf> {
+	let dirs = if privileged() {
+		SYSTEM_DATA_DIRS
+			.iter()
+			.map(PathBuf::from)
+			.collect::<Vec<_>>()
+	} else {
+		std::iter::once(glib::user_data_dir())
+			.chain(glib::system_data_dirs())
+			.collect()
+	};
+	let mut seen = HashSet::new();
+	let mut manifests = Vec::new();
+	for dir in dirs {
+		let entries = match fs::read_dir(dir.join("cosmic-settings").join("panels")) {
+			Ok(entries) => entries,
+			Err(_) => continue,
+		};
+		let mut paths = entries
+			.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+			.filter(|path| path.extension().map_or(false, |ext| ext == "toml"))
+			.filter(|path| !path.to_string_lossy().ends_with(".page.toml"))
+			.collect::<Vec<_>>();
+		paths.sort();
+		manifests.extend(
+			paths
+				.into_iter()
+				.filter(|path| seen.insert(path.file_name().map(ToOwned::to_owned))),
+		);
+	}
+	manifests
+}
+
+/// Whether the app runs with the privileges of another user or group than the one that
+/// started it, such as when it's installed setuid.
+fn privileged() -> bool {
+	// SAFETY: These only return the IDs of the process, and can't fail.
+	unsafe { libc::geteuid() != libc::getuid() || libc::getegid() != libc::getgid() }
+}
+
+async fn describe(helper: PathBuf) -> Result<String> {
+	let command = tokio::process::Command::new(&helper)
+		.arg("describe")
+		.kill_on_drop(true)
+		.output();
+	let output = tokio::time::timeout(DESCRIBE_TIMEOUT, command)
+		.await
+		.with_context(|| format!("'{}' took too long to describe its panel", helper.display()))?
+		.with_context(|| format!("failed to run '{}'", helper.display()))?;
+	if !output.status.success() {
+		bail!(
+			"'{}' failed with {}: {}",
+			helper.display(),
+			output.status,
+			String::from_utf8_lossy(&output.stderr).trim()
+		);
+	}
+	String::from_utf8(output.stdout).with_context(|| {
+		format!(
+			"'{}' printed a description that isn't UTF-8",
+			helper.display()
+		)
+	})
+}
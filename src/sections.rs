@@ -1,24 +1,147 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 mod about;
+mod declarative;
 mod desktop;
+mod history;
 mod keyboard;
+mod profiles;
 mod wifi;
 
 pub use self::{
-	about::AboutSection, desktop::DesktopSection, keyboard::KeyboardSection, wifi::WifiSection,
+	about::AboutSection,
+	declarative::{DeclarativeLayout, SectionDescription},
+	desktop::DesktopSection,
+	history::HistorySection,
+	keyboard::KeyboardSection,
+	profiles::ProfilesSection,
+	wifi::WifiSection,
 };
-use crate::ui::SettingsGui;
-use std::{cell::RefCell, rc::Rc};
+use crate::{
+	config::Key,
+	panels,
+	ui::{nav::Page, SettingsGui},
+};
+use once_cell::sync::Lazy;
+use std::{
+	cell::{Cell, RefCell},
+	rc::Rc,
+};
+use tokio::sync::watch;
 
 /// A section of the COSMIC settings app.
 pub trait Section: Sized {
-	/// The name this section appears as.
+	/// The name this section appears as, in English. Page paths are made from it, and it's
+	/// translated where it's shown.
 	const NAME: &'static str;
 	/// The icon that will appear next to this setting in the nav.
 	const ICON: &'static str;
 	/// Returns all the settings groups from this section.
 	fn layout() -> SectionLayout;
+	/// Returns whether this section can be used on this system.
+	/// Unavailable sections are hidden from the nav.
+	fn availability() -> Availability {
+		Availability::default()
+	}
+}
+
+/// Whether a [`Section`] or [`SettingsGroup`] can be used, such as whether there is hardware
+/// for it to configure.
+pub enum Availability {
+	/// Always available, or never.
+	Fixed(bool),
+	/// Available while the watched value is `true`, which may change while the app is running.
+	Changing(watch::Receiver<bool>),
+}
+
+impl Availability {
+	/// Calls `f` with whether this is available, now and every time that changes.
+	pub fn connect<F: Fn(bool) + 'static>(self, f: F) {
+		match self {
+			Self::Fixed(available) => f(available),
+			Self::Changing(mut receiver) => {
+				f(*receiver.borrow());
+				crate::task::spawn_local(async move {
+					while receiver.changed().await.is_ok() {
+						f(*receiver.borrow());
+					}
+				});
+			}
+		}
+	}
+}
+
+impl Default for Availability {
+	fn default() -> Self {
+		Self::Fixed(true)
+	}
+}
+
+/// The names of the built-in sections, which installed panels can't use.
+pub const BUILT_IN: [&str; 6] = [
+	WifiSection::NAME,
+	DesktopSection::NAME,
+	KeyboardSection::NAME,
+	ProfilesSection::NAME,
+	AboutSection::NAME,
+	HistorySection::NAME,
+];
+
+/// Returns the name and layout of every section, including installed panels, in the order they
+/// appear in the nav.
+pub fn layouts() -> Vec<(&'static str, SectionLayout)> {
+	let mut layouts = vec![
+		(WifiSection::NAME, WifiSection::layout()),
+		(DesktopSection::NAME, DesktopSection::layout()),
+		(KeyboardSection::NAME, KeyboardSection::layout()),
+	];
+	layouts.extend(
+		panels::load()
+			.into_iter()
+			.map(|panel| (panel.name, panel.layout.to_layout())),
+	);
+	layouts.push((ProfilesSection::NAME, ProfilesSection::layout()));
+	layouts.push((AboutSection::NAME, AboutSection::layout()));
+	layouts.push((HistorySection::NAME, HistorySection::layout()));
+	layouts
+}
+
+/// The keys of every section, which are only collected once since that creates every group.
+static KEYS: Lazy<Vec<(&'static str, Vec<&'static Key>)>> = Lazy::new(|| {
+	layouts()
+		.into_iter()
+		.filter_map(|(name, layout)| {
+			let groups = match layout {
+				SectionLayout::Single(groups) => groups,
+				SectionLayout::Multiple(pages) => {
+					pages.into_iter().flat_map(|(_, groups)| groups).collect()
+				}
+			};
+			let mut keys: Vec<&'static Key> = Vec::new();
+			for key in groups.iter().flat_map(|group| group.keys().iter().copied()) {
+				if !keys
+					.iter()
+					.any(|known| known.component == key.component && known.name == key.name)
+				{
+					keys.push(key);
+				}
+			}
+			(!keys.is_empty()).then(|| (name, keys))
+		})
+		.collect()
+});
+
+/// Returns the name of every section that has settings keys, along with its keys, in the
+/// order they appear in the nav.
+pub fn keys() -> Vec<(&'static str, Vec<&'static Key>)> {
+	KEYS.clone()
+}
+
+/// Returns every settings key of every section.
+pub fn all_keys() -> Vec<&'static Key> {
+	KEYS.iter()
+		.flat_map(|(_, keys)| keys.iter().copied())
+		.collect()
 }
 
 /// The layout of the groups in a [`Section`].
@@ -28,7 +151,8 @@ pub enum SectionLayout {
 	/// The section has multiple pages of settings groups.
 	///
 	/// This is a [`Vec`] and not a [`HashMap`] or [`BTreeMap`] due to the fact that they may
-	/// be arbitraily sorted in a specific way.
+	/// be arbitraily sorted in a specific way. Like [`Section::NAME`], the names of the pages
+	/// are in English.
 	Multiple(Vec<(&'static str, Vec<Box<dyn SettingsGroup>>)>),
 }
 
@@ -41,16 +165,109 @@ pub trait SettingsGroup {
 	{
 		Box::new(Self::default()) as _
 	}
-	/// Returns the title of this group of settings.
+	/// Returns the title of this group of settings, translated with [`tr`](crate::i18n::tr).
 	/// No title will be displayed if an empty string is returned.
-	fn title(&self) -> &'static str {
-		""
+	fn title(&self) -> String {
+		String::new()
+	}
+	/// Returns the keywords that this group will show up in search with, in English and the
+	/// user's language, such as those from [`keywords`](crate::i18n::keywords).
+	fn keywords(&self) -> Vec<String>;
+	/// Returns the settings keys that the widgets of this group are bound to.
+	/// These are restored to their defaults when the group is reset.
+	fn keys(&self) -> &'static [&'static Key] {
+		&[]
+	}
+	/// Returns whether this group can be used on this system.
+	/// Unavailable groups are hidden from their page and from search.
+	fn availability(&self) -> Availability {
+		Availability::default()
+	}
+	/// Describes the rows that [`layout`](Self::layout) creates, without needing a display,
+	/// for `cosmic-settings --dump-layout`. Groups whose rows depend on the system, such as a
+	/// list of networks, don't describe any.
+	fn rows(&self) -> Vec<Row> {
+		Vec::new()
 	}
-	/// Returns the keywords that this group will show up in search with.
-	fn keywords(&self) -> &'static [&'static str];
 	/// Lays out the widgets from this setting group in the given Box.
 	fn layout(&self, target: &gtk4::Box, ui: Rc<SettingsGui>);
 }
 
+/// A row of a [`SettingsGroup`], as described by [`SettingsGroup::rows`].
+pub struct Row {
+	/// The title of the row, translated with [`tr`](crate::i18n::tr).
+	pub title: String,
+	pub description: Option<String>,
+	pub control: Control,
+	/// The key that the control is bound to, if any.
+	pub key: Option<&'static Key>,
+}
+
+impl Row {
+	pub fn new(title: String, control: Control) -> Self {
+		Self {
+			title,
+			description: None,
+			control,
+			key: None,
+		}
+	}
+
+	pub fn description(mut self, description: String) -> Self {
+		self.description = Some(description);
+		self
+	}
+
+	pub fn key(mut self, key: &'static Key) -> Self {
+		self.key = Some(key);
+		self
+	}
+}
+
+/// The widget a [`Row`] shows its setting with.
+pub enum Control {
+	Switch,
+	/// A radio button for each value of the key, given along with its title.
+	Radio(Vec<(&'static str, String)>),
+	Spin,
+	/// A dropdown of the values of the key, given along with their titles.
+	Dropdown(Vec<(&'static str, String)>),
+	/// A button with the given label, which shows another page if it has a path.
+	Button {
+		label: String,
+		page: Option<String>,
+	},
+	/// Text that can't be changed, such as information about the system.
+	Text,
+}
+
+impl Control {
+	/// The name of the widget in `--dump-layout`, such as `switch`.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Switch => "switch",
+			Self::Radio(_) => "radio",
+			Self::Spin => "spin",
+			Self::Dropdown(_) => "dropdown",
+			Self::Button { .. } => "button",
+			Self::Text => "text",
+		}
+	}
+}
+
+/// A settings group that has been laid out on a page.
+pub struct PlacedGroup {
+	pub group: Box<dyn SettingsGroup>,
+	/// The page the group was laid out on.
+	pub page: Page,
+	/// The card containing the widgets of the group.
+	pub card: gtk4::Box,
+	/// Whether the group is currently available.
+	pub available: Rc<Cell<bool>>,
+	/// Whether the widgets of the group have been laid out, which happens the first time its
+	/// page is shown.
+	pub built: Cell<bool>,
+}
+
 /// Where all the settings groups are stored. Used for search.
-pub type SettingsGroupStore = Rc<RefCell<Vec<Box<dyn SettingsGroup>>>>;
+pub type SettingsGroupStore = Rc<RefCell<Vec<PlacedGroup>>>;
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use super::{Section, SectionLayout, SettingsGroup};
-use crate::ui::SettingsGui;
+use super::{Control, Row, Section, SectionLayout, SettingsGroup};
+use crate::{
+	i18n::{keywords, mark, tr},
+	ui::SettingsGui,
+};
 use bytesize::ByteSize;
 use gtk4::{prelude::*, Image, Label, Orientation};
 use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
@@ -14,7 +17,7 @@ thread_local!(static SYSTEM_INFO: System = System::new_all());
 pub struct AboutSection;
 
 impl Section for AboutSection {
-	const NAME: &'static str = "About";
+	const NAME: &'static str = mark("About");
 	const ICON: &'static str = "dialog-information-symbolic";
 
 	fn layout() -> SectionLayout {
@@ -30,8 +33,8 @@ impl Section for AboutSection {
 #[derive(Default)]
 struct PopIcon;
 impl SettingsGroup for PopIcon {
-	fn keywords(&self) -> &'static [&'static str] {
-		&[]
+	fn keywords(&self) -> Vec<String> {
+		Vec::new()
 	}
 
 	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
@@ -51,8 +54,12 @@ impl SettingsGroup for PopIcon {
 #[derive(Default)]
 struct Device;
 impl SettingsGroup for Device {
-	fn keywords(&self) -> &'static [&'static str] {
-		&["device", "hostname", "name", "computer"]
+	fn keywords(&self) -> Vec<String> {
+		keywords("device;hostname;name;computer")
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		vec![Row::new(tr("Device Name"), Control::Text)]
 	}
 
 	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
@@ -60,7 +67,7 @@ impl SettingsGroup for Device {
 			if let Some(hostname) = info.host_name() {
 				view! {
 					row = LabeledItem {
-						set_title: "Device Name",
+						set_title: &tr("Device Name"),
 						set_child: label = &Label {
 							set_text: &hostname
 						}
@@ -97,21 +104,20 @@ impl DeviceSpecs {
 }
 
 impl SettingsGroup for DeviceSpecs {
-	fn keywords(&self) -> &'static [&'static str] {
-		&[
-			"device",
-			"specs",
-			"hardware",
-			"cpu",
-			"memory",
-			"ram",
-			"storage",
-			"gpu",
-			"graphics",
-			"processor",
-			"disk",
-			"space",
+	fn keywords(&self) -> Vec<String> {
+		keywords("device;specs;hardware;cpu;memory;ram;storage;gpu;graphics;processor;disk;space")
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		[
+			tr("Memory"),
+			tr("Processor"),
+			tr("Graphics"),
+			tr("Disk Capacity"),
 		]
+		.into_iter()
+		.map(|title| Row::new(title, Control::Text))
+		.collect()
 	}
 
 	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
@@ -119,7 +125,7 @@ impl SettingsGroup for DeviceSpecs {
 			let memory = ByteSize::kb(info.total_memory());
 			view! {
 				memory_row = LabeledItem {
-					set_title: "Memory",
+					set_title: &tr("Memory"),
 					set_child: memory_label = &Label {
 						set_text: &memory.to_string_as(true)
 					}
@@ -129,7 +135,7 @@ impl SettingsGroup for DeviceSpecs {
 			let cpu = info.global_processor_info();
 			view! {
 				memory_row = LabeledItem {
-					set_title: "Processor",
+					set_title: &tr("Processor"),
 					set_child: memory_label = &Label {
 						set_text: cpu.name()
 					}
@@ -148,7 +154,7 @@ impl SettingsGroup for DeviceSpecs {
 				}
 				view! {
 					gpus_row = LabeledItem {
-						set_title: "Memory",
+						set_title: &tr("Graphics"),
 						set_child: &graphics_box
 					}
 				}
@@ -157,7 +163,7 @@ impl SettingsGroup for DeviceSpecs {
 			let disk = &info.disks()[0];
 			view! {
 				disk_row = LabeledItem {
-					set_title: "Disk Capacity",
+					set_title: &tr("Disk Capacity"),
 					set_child: disk_label = &Label {
 						set_text: &ByteSize::b(disk.total_space()).to_string_as(true)
 					}
@@ -172,23 +178,19 @@ impl SettingsGroup for DeviceSpecs {
 struct OsInfo;
 
 impl SettingsGroup for OsInfo {
-	fn keywords(&self) -> &'static [&'static str] {
-		&[
-			"x11",
-			"xorg",
-			"wayland",
-			"os",
-			"operating system",
-			"window",
-			"windowing system",
-			"64 bit",
-			"32 bit",
-			"64-bit",
-			"32-bit",
-			"x86",
-			"x64",
-			"x86_64",
+	fn keywords(&self) -> Vec<String> {
+		keywords("x11;xorg;wayland;os;operating system;window;windowing system;64 bit;32 bit;64-bit;32-bit;x86;x64;x86_64")
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		[
+			tr("Operating System"),
+			tr("OS Type"),
+			tr("Windowing System"),
 		]
+		.into_iter()
+		.map(|title| Row::new(title, Control::Text))
+		.collect()
 	}
 
 	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
@@ -196,7 +198,7 @@ impl SettingsGroup for OsInfo {
 			if let (Some(os_version), Some(os_name)) = (info.os_version(), info.name()) {
 				view! {
 					os_row = LabeledItem {
-						set_title: "Operating System",
+						set_title: &tr("Operating System"),
 						set_child: os_label = &Label {
 							set_text: &format!("{} {}", os_name, os_version)
 						}
@@ -206,34 +208,34 @@ impl SettingsGroup for OsInfo {
 			}
 
 			let os_type = if cfg!(target_arch = "aarch64") {
-				"64-bit (ARM)"
+				tr("64-bit (ARM)")
 			} else if cfg!(target_arch = "x86_64") {
-				"64-bit (x86)"
+				tr("64-bit (x86)")
 			} else if cfg!(target_arch = "x86") {
-				"32-bit (x86)"
+				tr("32-bit (x86)")
 			} else {
-				"Unknown"
+				tr("Unknown")
 			};
 			view! {
 				arch_row = LabeledItem {
-					set_title: "OS Type",
+					set_title: &tr("OS Type"),
 					set_child: arch_label = &Label {
-						set_text: os_type
+						set_text: &os_type
 					}
 				}
 			}
 			target.container_add(&arch_row);
 
 			let window_system = match std::env::var("XDG_SESSION_TYPE").as_deref() {
-				Ok("wayland") => "Wayland",
-				Ok("x11") => "X11",
-				_ => "Unknown",
+				Ok("wayland") => "Wayland".to_string(),
+				Ok("x11") => "X11".to_string(),
+				_ => tr("Unknown"),
 			};
 			view! {
 				window_system_row = LabeledItem {
-					set_title: "Windowing System",
+					set_title: &tr("Windowing System"),
 					set_child: window_label = &Label {
-						set_text: window_system
+						set_text: &window_system
 					}
 				}
 			}
@@ -0,0 +1,646 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Settings pages that are described in TOML, rather than written as a [`SettingsGroup`].
+//!
+//! ```toml
+//! [[group]]
+//! title = "Fans"
+//! keywords = ["fan", "cooling"]
+//!
+//! [[group.setting]]
+//! type = "radio"
+//! key = "acme-fans/profile"
+//! default = "balanced"
+//! options = [
+//! 	{ value = "quiet", title = "Quiet" },
+//! 	{ value = "balanced", title = "Balanced", description = "Recommended for most uses" },
+//! ]
+//!
+//! [[group.setting]]
+//! type = "switch"
+//! key = "acme-fans/boost"
+//! title = "Boost When Charging"
+//!
+//! [[group.setting]]
+//! type = "spin"
+//! key = "acme-fans/minimum-speed"
+//! title = "Minimum Speed"
+//! default = 20
+//! min = 0
+//! max = 100
+//!
+//! [[group.setting]]
+//! type = "dropdown"
+//! key = "acme-fans/curve"
+//! title = "Fan Curve"
+//! default = "linear"
+//! options = [
+//! 	{ value = "linear", title = "Linear" },
+//! 	{ value = "exponential", title = "Exponential" },
+//! ]
+//!
+//! [[group.setting]]
+//! type = "button"
+//! title = "Power Settings"
+//! label = "Open"
+//! page = "power"
+//! ```
+//!
+//! A section with several pages lists them as `[[page]]` tables with a `title`, each holding
+//! its own `[[page.group]]` tables.
+//!
+//! Besides its own `keywords`, a group shows up in search with the words of its title and the
+//! titles of its settings. Titles, descriptions, labels and keywords are translated with the
+//! app's catalog when they're in it.
+//!
+//! Pages and groups with `requires = "program"` are only shown when that program is installed.
+
+use super::{Availability, Control, Row, SectionLayout, SettingsGroup};
+use crate::{
+	a11y,
+	config::{bind, Key, KeyKind},
+	i18n::{self, tr},
+	ui::SettingsGui,
+};
+use anyhow::{bail, Context, Result};
+use gtk4::{
+	glib::{self, clone},
+	prelude::*,
+	Align, Button, CheckButton, DropDown, Orientation, SpinButton, Switch,
+};
+use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
+use serde::Deserialize;
+use std::rc::Rc;
+
+/// The description of every page in a section.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SectionDescription {
+	/// The groups of a section with a single page.
+	#[serde(default, rename = "group")]
+	groups: Vec<GroupDescription>,
+	/// The pages of a section with several pages.
+	#[serde(default, rename = "page")]
+	pages: Vec<PageDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PageDescription {
+	title: String,
+	requires: Option<String>,
+	#[serde(default, rename = "group")]
+	groups: Vec<GroupDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GroupDescription {
+	#[serde(default)]
+	title: String,
+	requires: Option<String>,
+	#[serde(default)]
+	keywords: Vec<String>,
+	#[serde(default, rename = "setting")]
+	settings: Vec<SettingDescription>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+enum SettingDescription {
+	/// A switch bound to a boolean key.
+	Switch {
+		key: String,
+		title: String,
+		description: Option<String>,
+		#[serde(default)]
+		default: bool,
+	},
+	/// A list of radio buttons bound to a choice key, one for each option.
+	Radio {
+		key: String,
+		default: String,
+		options: Vec<OptionDescription>,
+	},
+	/// A spin button bound to an integer key.
+	Spin {
+		key: String,
+		title: String,
+		description: Option<String>,
+		default: i64,
+		min: i64,
+		max: i64,
+	},
+	/// A drop-down list bound to a choice key, with an item for each option.
+	Dropdown {
+		key: String,
+		title: String,
+		description: Option<String>,
+		default: String,
+		options: Vec<OptionDescription>,
+	},
+	/// A button that shows another page, given its path such as `desktop/dock`.
+	Button {
+		title: String,
+		description: Option<String>,
+		label: String,
+		page: String,
+	},
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OptionDescription {
+	value: String,
+	title: String,
+	description: Option<String>,
+}
+
+impl SectionDescription {
+	/// Parses the description of a section from TOML.
+	pub fn parse(text: &str) -> Result<Self> {
+		let description: Self = toml::from_str(text)?;
+		if !description.groups.is_empty() && !description.pages.is_empty() {
+			bail!("a section must have either groups or pages, not both");
+		}
+		Ok(description)
+	}
+
+	/// Creates the groups of the section, laid out as described.
+	///
+	/// Keys named in the description are created here, and live for the rest of the program,
+	/// so each description should only be built once.
+	pub fn build(self) -> Result<DeclarativeLayout> {
+		if self.pages.is_empty() {
+			return Ok(DeclarativeLayout::Single(groups(self.groups, None)?));
+		}
+		let pages = self
+			.pages
+			.into_iter()
+			.map(|page| {
+				let groups = groups(page.groups, page.requires.as_deref())
+					.with_context(|| format!("invalid page '{}'", page.title))?;
+				Ok((leak(page.title), groups))
+			})
+			.collect::<Result<_>>()?;
+		Ok(DeclarativeLayout::Multiple(pages))
+	}
+}
+
+/// The groups of a section built from its description, which are copied into a new
+/// [`SectionLayout`] every time one is needed.
+#[derive(Clone)]
+pub enum DeclarativeLayout {
+	Single(Vec<DeclarativeGroup>),
+	Multiple(Vec<(&'static str, Vec<DeclarativeGroup>)>),
+}
+
+impl DeclarativeLayout {
+	pub fn to_layout(&self) -> SectionLayout {
+		let boxed = |groups: &[DeclarativeGroup]| {
+			groups
+				.iter()
+				.map(|group| Box::new(group.clone()) as Box<dyn SettingsGroup>)
+				.collect()
+		};
+		match self {
+			Self::Single(groups) => SectionLayout::Single(boxed(groups)),
+			Self::Multiple(pages) => SectionLayout::Multiple(
+				pages
+					.iter()
+					.map(|(title, groups)| (*title, boxed(groups)))
+					.collect(),
+			),
+		}
+	}
+
+	/// Returns the keys of every group.
+	pub fn keys(&self) -> Vec<&'static Key> {
+		let groups: Vec<_> = match self {
+			Self::Single(groups) => groups.iter().collect(),
+			Self::Multiple(pages) => pages.iter().flat_map(|(_, groups)| groups).collect(),
+		};
+		groups
+			.into_iter()
+			.flat_map(|group| group.keys.iter().copied())
+			.collect()
+	}
+}
+
+/// Creates the groups of a page, which require the given program in addition to their own.
+fn groups(
+	descriptions: Vec<GroupDescription>,
+	requires: Option<&str>,
+) -> Result<Vec<DeclarativeGroup>> {
+	descriptions
+		.into_iter()
+		.map(|mut description| {
+			if description.requires.is_none() {
+				description.requires = requires.map(ToOwned::to_owned);
+			}
+			let title = description.title.clone();
+			DeclarativeGroup::new(description).with_context(|| format!("invalid group '{}'", title))
+		})
+		.collect()
+}
+
+/// A settings group built from its [description](GroupDescription).
+#[derive(Clone)]
+pub struct DeclarativeGroup {
+	title: String,
+	requires: Option<String>,
+	keywords: Vec<String>,
+	keys: &'static [&'static Key],
+	settings: Vec<Setting>,
+}
+
+#[derive(Clone)]
+enum Setting {
+	Switch {
+		key: &'static Key,
+		title: String,
+		description: Option<String>,
+	},
+	Radio {
+		key: &'static Key,
+		options: Vec<(&'static str, String, Option<String>)>,
+	},
+	Spin {
+		key: &'static Key,
+		title: String,
+		description: Option<String>,
+		min: i64,
+		max: i64,
+	},
+	Dropdown {
+		key: &'static Key,
+		title: String,
+		description: Option<String>,
+		options: Vec<(&'static str, String)>,
+	},
+	Button {
+		title: String,
+		description: Option<String>,
+		label: String,
+		page: String,
+	},
+}
+
+impl DeclarativeGroup {
+	fn new(description: GroupDescription) -> Result<Self> {
+		let keywords = keywords(&description);
+		let mut keys = Vec::new();
+		let mut settings = Vec::new();
+		for setting in description.settings {
+			let setting = match setting {
+				SettingDescription::Switch {
+					key,
+					title,
+					description,
+					default,
+				} => Setting::Switch {
+					key: key_from_id(&key, KeyKind::Bool { default })?,
+					title,
+					description,
+				},
+				SettingDescription::Radio {
+					key,
+					default,
+					options,
+				} => Setting::Radio {
+					key: choice_key(&key, default, &options)?,
+					options: options
+						.into_iter()
+						.map(|option| (leak(option.value), option.title, option.description))
+						.collect(),
+				},
+				SettingDescription::Spin {
+					key,
+					title,
+					description,
+					default,
+					min,
+					max,
+				} => {
+					if !(min..=max).contains(&default) {
+						bail!("'{}' has a default outside of {}..={}", key, min, max);
+					}
+					Setting::Spin {
+						key: key_from_id(&key, KeyKind::Int { default, min, max })?,
+						title,
+						description,
+						min,
+						max,
+					}
+				}
+				SettingDescription::Dropdown {
+					key,
+					title,
+					description,
+					default,
+					options,
+				} => Setting::Dropdown {
+					key: choice_key(&key, default, &options)?,
+					title,
+					description,
+					options: options
+						.into_iter()
+						.map(|option| (leak(option.value), option.title))
+						.collect(),
+				},
+				SettingDescription::Button {
+					title,
+					description,
+					label,
+					page,
+				} => Setting::Button {
+					title,
+					description,
+					label,
+					page,
+				},
+			};
+			keys.extend(setting.key());
+			settings.push(setting);
+		}
+		Ok(Self {
+			title: description.title,
+			requires: description.requires,
+			keywords,
+			keys: Box::leak(keys.into_boxed_slice()),
+			settings,
+		})
+	}
+}
+
+impl Setting {
+	fn key(&self) -> Option<&'static Key> {
+		match self {
+			Self::Switch { key, .. }
+			| Self::Radio { key, .. }
+			| Self::Spin { key, .. }
+			| Self::Dropdown { key, .. } => Some(*key),
+			Self::Button { .. } => None,
+		}
+	}
+}
+
+impl SettingsGroup for DeclarativeGroup {
+	fn title(&self) -> String {
+		tr(&self.title)
+	}
+
+	fn keywords(&self) -> Vec<String> {
+		self.keywords.clone()
+	}
+
+	fn keys(&self) -> &'static [&'static Key] {
+		self.keys
+	}
+
+	fn availability(&self) -> Availability {
+		let installed = self.requires.as_ref().map_or(true, |program| {
+			glib::find_program_in_path(program).is_some()
+		});
+		Availability::Fixed(installed)
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		let row = |title: &str, description: &Option<String>, control| {
+			let row = Row::new(tr(title), control);
+			match description {
+				Some(description) => row.description(tr(description)),
+				None => row,
+			}
+		};
+		self.settings
+			.iter()
+			.map(|setting| match setting {
+				Setting::Switch {
+					key,
+					title,
+					description,
+				} => row(title, description, Control::Switch).key(*key),
+				Setting::Radio { key, options } => {
+					let options = options
+						.iter()
+						.map(|(value, title, _)| (*value, tr(title)))
+						.collect();
+					row(&self.title, &None, Control::Radio(options)).key(*key)
+				}
+				Setting::Spin {
+					key,
+					title,
+					description,
+					..
+				} => row(title, description, Control::Spin).key(*key),
+				Setting::Dropdown {
+					key,
+					title,
+					description,
+					options,
+				} => {
+					let options = options
+						.iter()
+						.map(|(value, title)| (*value, tr(title)))
+						.collect();
+					row(title, description, Control::Dropdown(options)).key(*key)
+				}
+				Setting::Button {
+					title,
+					description,
+					label,
+					page,
+				} => row(
+					title,
+					description,
+					Control::Button {
+						label: tr(label),
+						page: Some(page.clone()),
+					},
+				),
+			})
+			.collect()
+	}
+
+	fn layout(&self, target: &gtk4::Box, ui: Rc<SettingsGui>) {
+		let entry_box = gtk4::Box::new(Orientation::Vertical, 0);
+		for setting in &self.settings {
+			match setting {
+				Setting::Switch {
+					key,
+					title,
+					description,
+				} => {
+					let switch = Switch::builder().valign(Align::Center).build();
+					entry_box.container_add(&labeled_item(title, description, &switch));
+					bind::switch(&ui.config, &switch, *key);
+				}
+				Setting::Radio { key, options } => {
+					let radio_group = a11y::radio_group(&tr(&self.title));
+					let mut buttons = Vec::with_capacity(options.len());
+					let mut first: Option<CheckButton> = None;
+					for (index, (value, title, description)) in options.iter().enumerate() {
+						let check = a11y::radio_button();
+						check.set_group(first.as_ref());
+						first.get_or_insert_with(|| check.clone());
+						a11y::set_position(&check, index, options.len());
+						let item = labeled_item(title, description, &check);
+						item.set_alignment(Align::Start);
+						radio_group.container_add(&item);
+						buttons.push((check, *value));
+					}
+					entry_box.container_add(&radio_group);
+					let buttons = buttons
+						.iter()
+						.map(|(check, value)| (check, *value))
+						.collect::<Vec<_>>();
+					bind::radio(&ui.config, &buttons, *key);
+				}
+				Setting::Spin {
+					key,
+					title,
+					description,
+					min,
+					max,
+				} => {
+					let spin = SpinButton::with_range(*min as f64, *max as f64, 1.);
+					entry_box.container_add(&labeled_item(title, description, &spin));
+					bind::spin(&ui.config, &spin, *key);
+				}
+				Setting::Dropdown {
+					key,
+					title,
+					description,
+					options,
+				} => {
+					let labels = options
+						.iter()
+						.map(|(_, label)| tr(label))
+						.collect::<Vec<_>>();
+					let labels = labels.iter().map(String::as_str).collect::<Vec<_>>();
+					let dropdown = DropDown::from_strings(&labels);
+					dropdown.set_valign(Align::Center);
+					entry_box.container_add(&labeled_item(title, description, &dropdown));
+					bind::dropdown(&ui.config, &dropdown, *key);
+				}
+				Setting::Button {
+					title,
+					description,
+					label,
+					page,
+				} => {
+					let button = Button::builder()
+						.label(&tr(label))
+						.valign(Align::Center)
+						.css_classes(vec!["settings-button".into()])
+						.build();
+					let page = page.clone();
+					button.connect_clicked(clone!(@weak ui => move |_| {
+						if !ui.nav.show_page(&page) {
+							warn!(%page, "Button leads to an unknown page");
+						}
+					}));
+					entry_box.container_add(&labeled_item(title, description, &button));
+				}
+			}
+		}
+		target.append(&entry_box);
+	}
+}
+
+fn labeled_item(
+	title: &str,
+	description: &Option<String>,
+	child: &impl IsA<gtk4::Widget>,
+) -> LabeledItem {
+	let item = LabeledItem::default();
+	let child = child.upcast_ref::<gtk4::Widget>();
+	item.set_title(&tr(title));
+	a11y::set_label(child, &tr(title));
+	if let Some(description) = description {
+		item.set_description(&tr(description));
+		a11y::set_description(child, &tr(description));
+	}
+	item.set_child(child);
+	item
+}
+
+/// Returns the keywords a group shows up in search with: its own, followed by every word of
+/// its title and the titles of its settings, in English and the user's language.
+fn keywords(description: &GroupDescription) -> Vec<String> {
+	let mut titles = vec![description.title.as_str()];
+	for setting in &description.settings {
+		match setting {
+			SettingDescription::Switch { title, .. }
+			| SettingDescription::Spin { title, .. }
+			| SettingDescription::Button { title, .. } => titles.push(title),
+			SettingDescription::Dropdown { title, options, .. } => {
+				titles.push(title);
+				titles.extend(options.iter().map(|option| option.title.as_str()));
+			}
+			SettingDescription::Radio { options, .. } => {
+				titles.extend(options.iter().map(|option| option.title.as_str()));
+			}
+		}
+	}
+	let translated = titles.iter().map(|title| tr(title)).collect::<Vec<_>>();
+	let words = titles
+		.into_iter()
+		.chain(translated.iter().map(String::as_str))
+		.flat_map(|title| title.split(|c: char| !c.is_alphanumeric()))
+		.filter(|word| word.chars().count() > 2)
+		.map(str::to_lowercase);
+	let mut keywords = description
+		.keywords
+		.iter()
+		.flat_map(|keyword| i18n::keywords(keyword))
+		.collect::<Vec<_>>();
+	for word in words {
+		if !keywords.contains(&word) {
+			keywords.push(word);
+		}
+	}
+	keywords
+}
+
+/// Creates a choice key whose choices are the values of the given options.
+fn choice_key(id: &str, default: String, options: &[OptionDescription]) -> Result<&'static Key> {
+	if !options.iter().any(|option| option.value == default) {
+		bail!("'{}' has no option for its default '{}'", id, default);
+	}
+	let choices = options
+		.iter()
+		.map(|option| leak(option.value.clone()))
+		.collect::<Vec<_>>();
+	let kind = KeyKind::Choice {
+		default: leak(default),
+		choices: Box::leak(choices.into_boxed_slice()),
+	};
+	key_from_id(id, kind)
+}
+
+/// Creates a key from its `component/name` form.
+fn key_from_id(id: &str, kind: KeyKind) -> Result<&'static Key> {
+	// Keys name files in the config directory, so they mustn't be able to leave it.
+	let valid = |part: &str| {
+		!part.is_empty()
+			&& !part.starts_with('.')
+			&& part
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+	};
+	let (component, name) = match id.split_once('/') {
+		Some((component, name)) if valid(component) && valid(name) => (component, name),
+		_ => bail!("'{}' is not a key of the form 'component/name'", id),
+	};
+	Ok(Box::leak(Box::new(Key {
+		component: leak(component.to_string()),
+		name: leak(name.to_string()),
+		kind,
+	})))
+}
+
+fn leak(text: String) -> &'static str {
+	Box::leak(text.into_boxed_str())
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{Section, SectionLayout, SettingsGroup};
+use crate::{
+	a11y,
+	i18n::{keywords, mark, tr},
+	ui::SettingsGui,
+};
+use gtk4::{
+	glib::{self, clone},
+	prelude::*,
+	Align, Button, Label,
+};
+use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
+use std::rc::Rc;
+
+pub struct HistorySection;
+
+impl Section for HistorySection {
+	const NAME: &'static str = mark("Recent Changes");
+	const ICON: &'static str = "document-open-recent-symbolic";
+
+	fn layout() -> SectionLayout {
+		SectionLayout::Single(vec![RecentChanges::boxed()])
+	}
+}
+
+#[derive(Default)]
+struct RecentChanges;
+
+impl RecentChanges {
+	/// How many of the most recent changes are listed.
+	const LIMIT: usize = 50;
+
+	fn populate(target: &gtk4::Box, ui: &Rc<SettingsGui>) {
+		while let Some(child) = target.first_child() {
+			target.remove(&child);
+		}
+		let entries = ui.config.history().entries();
+		if entries.is_empty() {
+			let label = Label::builder()
+				.label(&tr("No settings have been changed yet"))
+				.css_classes(vec!["settings-entry-text".into()])
+				.build();
+			target.append(&label);
+			return;
+		}
+		for entry in entries.into_iter().rev().take(Self::LIMIT) {
+			let title = format!("{}: {} → {}", entry.key, entry.old, entry.new);
+			let description = format!("{} · {}", entry.local_time(), entry.source);
+			view! {
+				row = LabeledItem {
+					set_title: title.as_str(),
+					set_description: description.as_str(),
+					set_child: revert = &Button {
+						add_css_class: "settings-button",
+						set_label: &tr("Revert"),
+						set_valign: Align::Center
+					}
+				}
+			}
+			// Every row has a button with the same label, so say what it reverts.
+			a11y::set_description(&revert, &title);
+			revert.connect_clicked(clone!(@strong ui => move |_| {
+				if let Err(err) = ui.config.revert(&entry, &super::all_keys()) {
+					error!(?err, key = %entry.key, "Failed to revert setting");
+					ui.notifications.toast(
+						&tr("Couldn't revert {key}: {error}")
+							.replace("{key}", &entry.key)
+							.replace("{error}", &format!("{:#}", err)),
+						None,
+					);
+				}
+			}));
+			target.container_add(&row);
+		}
+	}
+}
+
+impl SettingsGroup for RecentChanges {
+	fn title(&self) -> String {
+		tr("Recent Changes")
+	}
+
+	fn keywords(&self) -> Vec<String> {
+		keywords("history;recent;changes;undo;revert;log")
+	}
+
+	fn layout(&self, target: &gtk4::Box, ui: Rc<SettingsGui>) {
+		let entries_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+		Self::populate(&entries_box, &ui);
+		ui.config.connect_recorded(
+			clone!(@weak entries_box, @weak ui => move |_| Self::populate(&entries_box, &ui)),
+		);
+		target.append(&entries_box);
+	}
+}
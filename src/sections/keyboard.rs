@@ -1,72 +1,116 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use super::{Section, SectionLayout, SettingsGroup};
-use crate::ui::SettingsGui;
-use gtk4::{prelude::*, Align, CheckButton, Label, Orientation};
+use super::{Control, Row, Section, SectionLayout, SettingsGroup};
+use crate::{
+	a11y,
+	config::{bind, Key, KeyKind},
+	i18n::{keywords, mark, tr},
+	ui::SettingsGui,
+};
+use gtk4::{prelude::*, Align, Label, Orientation};
 use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
 use std::rc::Rc;
 
 pub struct KeyboardSection;
 
 impl Section for KeyboardSection {
-	const NAME: &'static str = "Keyboard";
+	const NAME: &'static str = mark("Keyboard");
 	const ICON: &'static str = "preferences-desktop-keyboard-symbolic";
 
 	fn layout() -> SectionLayout {
 		SectionLayout::Multiple(vec![
 			(
-				"Input",
+				mark("Input"),
 				vec![
 					InputSourceSwitching::boxed(),
 					TypeSpecialCharacters::boxed(),
 				],
 			),
-			("Keyboard Shortcuts", vec![]),
+			(mark("Keyboard Shortcuts"), vec![]),
 		])
 	}
 }
 
+const INPUT_SOURCE_SCOPE: Key = Key {
+	component: "cosmic-comp",
+	name: "input-source-scope",
+	kind: KeyKind::Choice {
+		default: "global",
+		choices: &["global", "per-window"],
+	},
+};
+
 #[derive(Default)]
 struct InputSourceSwitching;
 
 impl SettingsGroup for InputSourceSwitching {
-	fn title(&self) -> &'static str {
-		"Input Source Switching"
+	fn title(&self) -> String {
+		tr("Input Source Switching")
 	}
 
-	fn keywords(&self) -> &'static [&'static str] {
-		&["input", "source", "switch", "shortcut", "keyboard"]
+	fn keywords(&self) -> Vec<String> {
+		keywords("input;source;switch;shortcut;keyboard")
 	}
 
-	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
+	fn keys(&self) -> &'static [&'static Key] {
+		&[&INPUT_SOURCE_SCOPE]
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		let options = vec![
+			("global", tr("Use the same source for all windows")),
+			(
+				"per-window",
+				tr("Switch input sources individually for each window"),
+			),
+		];
+		vec![
+			Row::new(self.title(), Control::Radio(options)).key(&INPUT_SOURCE_SCOPE),
+			Row::new(tr("Keyboard Shortcut"), Control::Text)
+				.description(tr("This can be changed in Shortcuts")),
+		]
+	}
+
+	fn layout(&self, target: &gtk4::Box, ui: Rc<SettingsGui>) {
+		let same_title = tr("Use the same source for all windows");
+		let switch_title = tr("Switch input sources individually for each window");
 		view! {
 			entry_box = gtk4::Box {
 				set_orientation: Orientation::Vertical,
-				container_add: same_source = &LabeledItem {
-					set_title: "Use the same source for all windows",
-					set_alignment: Align::Start,
-					set_child: same_check = &CheckButton {
-						set_valign: Align::Center
-					}
-				},
-				container_add: switch_source = &LabeledItem {
-					set_title: "Switch input sources individually for each window",
-					set_alignment: Align::Start,
-					set_child: switch_check = &CheckButton {
-						set_valign: Align::Center,
-						set_group: Some(&same_check)
+				container_add: scope_group = &a11y::radio_group(&self.title()) {
+					container_add: same_source = &LabeledItem {
+						set_title: &same_title,
+						set_alignment: Align::Start,
+						set_child: same_check = &a11y::radio_button() {}
+					},
+					container_add: switch_source = &LabeledItem {
+						set_title: &switch_title,
+						set_alignment: Align::Start,
+						set_child: switch_check = &a11y::radio_button() {
+							set_group: Some(&same_check)
+						}
 					}
 				},
 				container_add: kb_shortcut = &LabeledItem {
-					set_title: "Keyboard Shortcut",
-					set_description: "This can be changed in Shortcuts",
+					set_title: &tr("Keyboard Shortcut"),
+					set_description: &tr("This can be changed in Shortcuts"),
 					set_alignment: Align::Start,
 					set_child: kb_label = &Label {
-						set_text: "Super+Space"
+						set_text: &tr("Super+Space")
 					}
 				}
 			}
 		}
+		let options = [(&same_check, &same_title), (&switch_check, &switch_title)];
+		for (index, (check, title)) in options.iter().enumerate() {
+			a11y::set_label(*check, title);
+			a11y::set_position(*check, index, options.len());
+		}
+		bind::radio(
+			&ui.config,
+			&[(&same_check, "global"), (&switch_check, "per-window")],
+			&INPUT_SOURCE_SCOPE,
+		);
 		target.append(&entry_box);
 	}
 }
@@ -75,19 +119,19 @@ impl SettingsGroup for InputSourceSwitching {
 struct TypeSpecialCharacters;
 
 impl SettingsGroup for TypeSpecialCharacters {
-	fn title(&self) -> &'static str {
-		"Type Special Characters"
+	fn title(&self) -> String {
+		tr("Type Special Characters")
+	}
+
+	fn keywords(&self) -> Vec<String> {
+		keywords("type;special;characters;unicode;utf;alternate char;compose")
 	}
 
-	fn keywords(&self) -> &'static [&'static str] {
-		&[
-			"type",
-			"special",
-			"characters",
-			"unicode",
-			"utf",
-			"alternate char",
-			"compose",
+	fn rows(&self) -> Vec<Row> {
+		vec![
+			Row::new(tr("Alternate Characters Key"), Control::Text)
+				.description(tr("Hold down and type to enter different characters")),
+			Row::new(tr("Compose Key"), Control::Text),
 		]
 	}
 
@@ -96,16 +140,16 @@ impl SettingsGroup for TypeSpecialCharacters {
 			entry_box = gtk4::Box {
 				set_orientation: Orientation::Vertical,
 				container_add: alt_characters = &LabeledItem {
-					set_title: "Alternate Characters Key",
-					set_description: "Hold down and type to enter different characters",
+					set_title: &tr("Alternate Characters Key"),
+					set_description: &tr("Hold down and type to enter different characters"),
 					set_child: alt_label = &Label {
-						set_text: "Layout Default"
+						set_text: &tr("Layout Default")
 					}
 				},
 				container_add: compose_characters = &LabeledItem {
-					set_title: "Compose Key",
+					set_title: &tr("Compose Key"),
 					set_child: compose_label = &Label {
-						set_text: "Layout Default"
+						set_text: &tr("Layout Default")
 					}
 				}
 			}
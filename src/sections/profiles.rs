@@ -0,0 +1,402 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{Control, Row, Section, SectionLayout, SettingsGroup};
+use crate::{
+	a11y,
+	config::{Key, KeyKind, Profile, Profiles, Value},
+	i18n::{keywords, mark, tr, tr_n},
+	task::report_error,
+	ui::{notifications::Action, popup::PopupDialog, SettingsGui},
+};
+use anyhow::{bail, Result};
+use gtk4::{
+	glib::clone, prelude::*, Align, Button, DropDown, Entry, Orientation, PolicyType,
+	ScrolledWindow, SpinButton, Switch, Widget,
+};
+use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
+use std::{cell::RefCell, rc::Rc};
+
+pub struct ProfilesSection;
+
+impl Section for ProfilesSection {
+	const NAME: &'static str = mark("Profiles");
+	const ICON: &'static str = "view-list-symbolic";
+
+	fn layout() -> SectionLayout {
+		SectionLayout::Single(vec![ProfileList::boxed()])
+	}
+}
+
+/// A key being edited in the profile popup, along with a function that reads its chosen value.
+struct EditedValue {
+	key: &'static Key,
+	value: Box<dyn Fn() -> Value>,
+	row: LabeledItem,
+}
+
+/// A row of the profile list, whose description and toggle button change when its profile is
+/// activated or deactivated.
+struct ProfileRow {
+	name: String,
+	/// How many settings the profile has, which the description starts with.
+	count: String,
+	row: LabeledItem,
+	toggle: Button,
+}
+
+#[derive(Default)]
+struct ProfileList;
+
+impl ProfileList {
+	fn populate(
+		target: &gtk4::Box,
+		ui: &Rc<SettingsGui>,
+		profiles: &Rc<Profiles>,
+		rows: &Rc<RefCell<Vec<ProfileRow>>>,
+	) {
+		while let Some(child) = target.first_child() {
+			target.remove(&child);
+		}
+		rows.borrow_mut().clear();
+		let list = match profiles.list() {
+			Ok(list) => list,
+			Err(err) => {
+				report_error(target, &err.context(tr("Couldn't read the profiles")));
+				return;
+			}
+		};
+		for profile in list {
+			let count = tr_n("{count} setting", "{count} settings", profile.values.len())
+				.replace("{count}", &profile.values.len().to_string());
+			view! {
+				row = LabeledItem {
+					set_title: &profile.name,
+					set_child: buttons = &gtk4::Box {
+						set_orientation: Orientation::Horizontal,
+						set_spacing: 8,
+						set_valign: Align::Center,
+						append: toggle = &Button {
+							add_css_class: "settings-button"
+						},
+						append: edit = &Button {
+							add_css_class: "settings-button",
+							set_label: &tr("Edit")
+						},
+						append: delete = &Button {
+							add_css_class: "settings-button",
+							set_label: &tr("Delete")
+						}
+					}
+				}
+			}
+			// Every row has buttons with the same labels, so say which profile they're for.
+			for button in [&toggle, &edit, &delete] {
+				a11y::set_description(button, &profile.name);
+			}
+			let profile = Rc::new(profile);
+			toggle.connect_clicked(
+				clone!(@weak target, @weak rows, @strong ui, @strong profiles, @strong profile => move |_| {
+					let result = if profiles.active().as_deref() == Some(profile.name.as_str()) {
+						profiles.deactivate(&ui.config).map_err(|err| {
+							err.context(tr("Couldn't deactivate {name}").replace("{name}", &profile.name))
+						})
+					} else {
+						profiles.activate(&ui.config, &profile).map_err(|err| {
+							err.context(tr("Couldn't activate {name}").replace("{name}", &profile.name))
+						})
+					};
+					if let Err(err) = result {
+						report_error(&target, &err);
+					}
+					// Nothing is recorded if the profile doesn't change any values.
+					Self::update_active(&rows.borrow(), profiles.active().as_deref());
+				}),
+			);
+			edit.connect_clicked(
+				clone!(@weak target, @weak rows, @strong ui, @strong profiles, @strong profile => move |_| {
+					crate::task::spawn_local(Self::edit(
+						target,
+						ui.clone(),
+						profiles.clone(),
+						rows,
+						Some((*profile).clone()),
+					));
+				}),
+			);
+			delete.connect_clicked(
+				clone!(@weak target, @weak rows, @strong ui, @strong profiles, @strong profile => move |_| {
+					if let Err(err) = profiles.remove(&ui.config, &profile.name) {
+						let context = tr("Couldn't delete {name}").replace("{name}", &profile.name);
+						report_error(&target, &err.context(context));
+						return;
+					}
+					Self::populate(&target, &ui, &profiles, &rows);
+					let undo = Action::new(
+						&tr("Undo"),
+						clone!(@weak target, @weak rows, @strong ui, @strong profiles, @strong profile => move || {
+							if let Err(err) = profiles.save(&ui.config, &profile) {
+								let context = tr("Couldn't restore {name}").replace("{name}", &profile.name);
+								report_error(&target, &err.context(context));
+							}
+							Self::populate(&target, &ui, &profiles, &rows);
+						}),
+					);
+					ui.notifications.toast(
+						&tr("{name} was deleted").replace("{name}", &profile.name),
+						Some(undo),
+					);
+				}),
+			);
+			target.container_add(&row);
+			rows.borrow_mut().push(ProfileRow {
+				name: profile.name.clone(),
+				count,
+				row,
+				toggle,
+			});
+		}
+		Self::update_active(&rows.borrow(), profiles.active().as_deref());
+	}
+
+	/// Shows which profile is active in the description and toggle button of each row.
+	fn update_active(rows: &[ProfileRow], active: Option<&str>) {
+		for row in rows {
+			if active == Some(row.name.as_str()) {
+				row.row
+					.set_description(&tr("Active · {count}").replace("{count}", &row.count));
+				row.toggle.set_label(&tr("Deactivate"));
+			} else {
+				row.row.set_description(&row.count);
+				row.toggle.set_label(&tr("Activate"));
+			}
+		}
+	}
+
+	/// Shows a popup for editing a profile, or for creating one if none is given, and saves
+	/// it once it's confirmed.
+	async fn edit(
+		target: gtk4::Box,
+		ui: Rc<SettingsGui>,
+		profiles: Rc<Profiles>,
+		rows: Rc<RefCell<Vec<ProfileRow>>>,
+		profile: Option<Profile>,
+	) {
+		let original = profile.as_ref().map(|profile| profile.name.clone());
+		let values = profile.map(|profile| profile.values).unwrap_or_default();
+		let ids = profiles
+			.keys()
+			.iter()
+			.map(|key| key.to_string())
+			.collect::<Vec<_>>();
+		let id_strs = ids.iter().map(String::as_str).collect::<Vec<_>>();
+		view! {
+			form = gtk4::Box {
+				set_orientation: Orientation::Vertical,
+				set_spacing: 16,
+				append: name = &Entry {
+					set_placeholder_text: Some(&tr("Profile Name")),
+					set_text: original.as_deref().unwrap_or_default(),
+					set_activates_default: true
+				},
+				append: scroll = &ScrolledWindow {
+					set_hscrollbar_policy: PolicyType::Never,
+					set_max_content_height: 320,
+					set_propagate_natural_height: true,
+					set_child: rows = Some(&gtk4::Box) {
+						set_orientation: Orientation::Vertical
+					}
+				},
+				append: add_box = &gtk4::Box {
+					set_orientation: Orientation::Horizontal,
+					set_spacing: 8,
+					append: key_choice = &DropDown::from_strings(&id_strs) {
+						set_hexpand: true
+					},
+					append: add = &Button {
+						add_css_class: "settings-button",
+						set_label: &tr("Add Setting")
+					}
+				}
+			}
+		}
+		a11y::set_label(&name, &tr("Profile Name"));
+		a11y::set_label(&scroll, &tr("Settings"));
+		a11y::set_label(&key_choice, &tr("Setting to Add"));
+
+		let edited = Rc::new(RefCell::new(Vec::<EditedValue>::new()));
+		let add_value = Rc::new(
+			clone!(@weak rows, @strong edited => move |key: &'static Key, value: &Value| {
+				if edited.borrow().iter().any(|edited| std::ptr::eq(edited.key, key)) {
+					return;
+				}
+				let id = key.to_string();
+				let (editor, read) = value_editor(key, value);
+				a11y::set_label(&editor, &id);
+				let remove = Button::builder()
+					.icon_name("list-remove-symbolic")
+					.tooltip_text(&tr("Remove"))
+					.valign(Align::Center)
+					.build();
+				a11y::set_label(&remove, &tr("Remove {key}").replace("{key}", &id));
+				view! {
+					row = LabeledItem {
+						set_title: &id,
+						set_child: row_box = &gtk4::Box {
+							set_orientation: Orientation::Horizontal,
+							set_spacing: 8,
+							append: &editor,
+							append: &remove
+						}
+					}
+				}
+				remove.connect_clicked(clone!(@weak rows, @weak row, @weak edited => move |_| {
+					edited.borrow_mut().retain(|edited| edited.row != row);
+					rows.remove(&row);
+				}));
+				rows.container_add(&row);
+				edited.borrow_mut().push(EditedValue { key, value: read, row });
+			}),
+		);
+		for (key, value) in &values {
+			add_value(*key, value);
+		}
+		add.connect_clicked(
+			clone!(@weak key_choice, @strong ui, @strong profiles, @strong add_value => move |_| {
+				if let Some(key) = profiles.keys().get(key_choice.selected() as usize) {
+					add_value(*key, &ui.config.get(key));
+				}
+			}),
+		);
+
+		let title = if original.is_some() {
+			tr("Edit Profile")
+		} else {
+			tr("New Profile")
+		};
+		let dialog = PopupDialog::new(&title, &form).default_button(
+			&tr("Save"),
+			clone!(@strong name, @strong edited => move || Profile {
+				name: name.text().trim().to_string(),
+				values: edited
+					.borrow()
+					.iter()
+					.map(|edited| (edited.key, (edited.value)()))
+					.collect(),
+			}),
+		);
+		let profile = match ui.popup.run(dialog).await {
+			Some(profile) => profile,
+			None => return,
+		};
+		if let Err(err) = Self::save(&ui, &profiles, original.as_deref(), &profile) {
+			let context = tr("Couldn't save {name}").replace("{name}", &profile.name);
+			report_error(&target, &err.context(context));
+		}
+		Self::populate(&target, &ui, &profiles, &rows);
+	}
+
+	/// Saves an edited profile, replacing the one it was edited from if it was renamed.
+	fn save(
+		ui: &SettingsGui,
+		profiles: &Profiles,
+		original: Option<&str>,
+		profile: &Profile,
+	) -> Result<()> {
+		if original != Some(profile.name.as_str())
+			&& profiles
+				.list()?
+				.iter()
+				.any(|existing| existing.name == profile.name)
+		{
+			bail!(tr("a profile named '{name}' already exists").replace("{name}", &profile.name));
+		}
+		profiles.save(&ui.config, profile)?;
+		if let Some(original) = original.filter(|original| *original != profile.name) {
+			let was_active = profiles.active().as_deref() == Some(original);
+			profiles.remove(&ui.config, original)?;
+			if was_active {
+				profiles.activate(&ui.config, profile)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl SettingsGroup for ProfileList {
+	fn title(&self) -> String {
+		tr("Profiles")
+	}
+
+	fn keywords(&self) -> Vec<String> {
+		keywords("profile;profiles;presentation;mode;travel;desk;docked;overlay;switch")
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		let control = Control::Button {
+			label: tr("New Profile"),
+			page: None,
+		};
+		vec![Row::new(tr("New Profile"), control)]
+	}
+
+	fn layout(&self, target: &gtk4::Box, ui: Rc<SettingsGui>) {
+		let profiles = Rc::new(Profiles::new(super::all_keys()));
+		let rows = Rc::new(RefCell::new(Vec::new()));
+		let list = gtk4::Box::new(Orientation::Vertical, 0);
+		Self::populate(&list, &ui, &profiles, &rows);
+		// Activating a profile from the command line changes settings too. This keeps the
+		// rows alive, which the buttons in them only hold weakly.
+		ui.config
+			.connect_recorded(clone!(@strong rows, @strong profiles => move |_| {
+				Self::update_active(&rows.borrow(), profiles.active().as_deref());
+			}));
+		let new = Button::builder()
+			.label(&tr("New Profile"))
+			.halign(Align::End)
+			.css_classes(vec!["settings-button".into()])
+			.build();
+		new.connect_clicked(
+			clone!(@weak list, @weak rows, @strong ui, @strong profiles => move |_| {
+				crate::task::spawn_local(Self::edit(list, ui.clone(), profiles.clone(), rows, None));
+			}),
+		);
+		target.append(&list);
+		target.append(&new);
+	}
+}
+
+/// Creates a widget for choosing a value of a key, along with a function that reads the
+/// chosen value.
+fn value_editor(key: &'static Key, value: &Value) -> (Widget, Box<dyn Fn() -> Value>) {
+	match key.kind {
+		KeyKind::Bool { .. } => {
+			let switch = Switch::builder()
+				.active(value.as_bool().unwrap_or_default())
+				.valign(Align::Center)
+				.build();
+			let read = clone!(@strong switch => move || Value::Bool(switch.is_active()));
+			(switch.upcast(), Box::new(read))
+		}
+		KeyKind::Int { min, max, default } => {
+			let spin = SpinButton::with_range(min as f64, max as f64, 1.);
+			spin.set_value(value.as_int().unwrap_or(default) as f64);
+			spin.set_valign(Align::Center);
+			let read = clone!(@strong spin => move || Value::Int(spin.value() as i64));
+			(spin.upcast(), Box::new(read))
+		}
+		KeyKind::Choice { default, choices } => {
+			let dropdown = DropDown::from_strings(choices);
+			let selected = choices
+				.iter()
+				.position(|choice| Some(*choice) == value.as_choice())
+				.unwrap_or_default();
+			dropdown.set_selected(selected as u32);
+			dropdown.set_valign(Align::Center);
+			let read = clone!(@strong dropdown => move || {
+				let choice = choices.get(dropdown.selected() as usize).unwrap_or(&default);
+				Value::Choice(choice.to_string())
+			});
+			(dropdown.upcast(), Box::new(read))
+		}
+	}
+}
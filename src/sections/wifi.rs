@@ -3,16 +3,25 @@
 mod saved_networks;
 mod visible_networks;
 
-use super::{Section, SectionLayout, SettingsGroup};
-use crate::ui::SettingsGui;
-use gtk4::{glib, prelude::*, Align, Button, Label, Orientation, Switch};
+use super::{Availability, Control, Row, Section, SectionLayout, SettingsGroup};
+use crate::{
+	a11y,
+	i18n::{keywords, mark, tr},
+	ui::{popup::PopupDialog, SettingsGui},
+};
+use cosmic_dbus_networkmanager::{device::SpecificDevice, nm::NetworkManager};
+use futures::StreamExt;
+use gtk4::{glib, prelude::*, Align, Button, Entry, Orientation, PasswordEntry, Switch};
 use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
-use std::rc::Rc;
+use std::{cell::Cell, collections::HashMap, rc::Rc};
+use tokio::sync::watch;
+use zbus::{Connection, Proxy};
+use zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
 
 pub struct WifiSection;
 
 impl Section for WifiSection {
-	const NAME: &'static str = "WiFi";
+	const NAME: &'static str = mark("WiFi");
 	const ICON: &'static str = "network-wireless-symbolic";
 
 	fn layout() -> SectionLayout {
@@ -24,30 +33,143 @@ impl Section for WifiSection {
 			saved_networks::SavedNetworks::boxed(),
 		])
 	}
+
+	fn availability() -> Availability {
+		// Shown until NetworkManager says otherwise, so that the page doesn't appear late.
+		let (tx, rx) = watch::channel(true);
+		crate::task::spawn(async move {
+			if let Err(err) = watch_wireless_devices(&tx).await {
+				error!(?err, "Failed to watch for wireless devices");
+			}
+		});
+		Availability::Changing(rx)
+	}
+}
+
+/// Keeps `tx` up to date with whether NetworkManager has a wireless device, such as when a USB
+/// adapter is plugged in or removed.
+async fn watch_wireless_devices(tx: &watch::Sender<bool>) -> anyhow::Result<()> {
+	let connection = Connection::system().await?;
+	let nm = NetworkManager::new(&connection).await?;
+	let proxy = Proxy::new(
+		&connection,
+		"org.freedesktop.NetworkManager",
+		"/org/freedesktop/NetworkManager",
+		"org.freedesktop.NetworkManager",
+	)
+	.await?;
+	let added = proxy.receive_signal("DeviceAdded").await?;
+	let removed = proxy.receive_signal("DeviceRemoved").await?;
+	let mut changes = Box::pin(futures::stream::select(added, removed));
+	loop {
+		if tx.send(has_wireless_device(&nm).await?).is_err() {
+			// The app no longer cares.
+			return Ok(());
+		}
+		if changes.next().await.is_none() {
+			return Ok(());
+		}
+	}
+}
+
+/// Binds a switch to radios of NetworkManager, named by their property such as
+/// `WirelessEnabled`, which are all turned on or off with it. An `inverted` switch is on while
+/// every radio is off, as airplane mode is.
+fn bind_radios(switch: &Switch, radios: &'static [&'static str], inverted: bool) {
+	// Set while the switch is updated from NetworkManager, so that it isn't written back.
+	let updating = Rc::new(Cell::new(false));
+	let tx = crate::task::ui_channel(
+		switch,
+		glib::clone!(@weak switch, @strong updating => move |enabled: bool| {
+			updating.set(true);
+			switch.set_active(enabled != inverted);
+			updating.set(false);
+		}),
+	);
+	crate::task::spawn_scoped(switch, async move {
+		let connection = Connection::system().await?;
+		let properties = properties_proxy(&connection).await?;
+		let mut changes = properties.receive_signal("PropertiesChanged").await?;
+		loop {
+			let mut enabled = false;
+			for radio in radios {
+				let value: OwnedValue = properties
+					.call("Get", &("org.freedesktop.NetworkManager", *radio))
+					.await?;
+				enabled |= bool::try_from(value)?;
+			}
+			if tx.send(enabled).is_err() || changes.next().await.is_none() {
+				return Ok(());
+			}
+		}
+	});
+	switch.connect_active_notify(move |switch| {
+		if updating.get() {
+			return;
+		}
+		let enabled = switch.is_active() != inverted;
+		crate::task::spawn_scoped(switch, async move {
+			let connection = Connection::system().await?;
+			let properties = properties_proxy(&connection).await?;
+			for radio in radios {
+				let value = Value::from(enabled);
+				let () = properties
+					.call("Set", &("org.freedesktop.NetworkManager", *radio, value))
+					.await?;
+			}
+			Ok(())
+		});
+	});
+}
+
+async fn properties_proxy(connection: &Connection) -> zbus::Result<Proxy<'_>> {
+	Proxy::new(
+		connection,
+		"org.freedesktop.NetworkManager",
+		"/org/freedesktop/NetworkManager",
+		"org.freedesktop.DBus.Properties",
+	)
+	.await
+}
+
+async fn has_wireless_device(nm: &NetworkManager<'_>) -> anyhow::Result<bool> {
+	for device in nm.devices().await? {
+		if let Ok(Some(SpecificDevice::Wireless(_))) = device.downcast_to_device().await {
+			return Ok(true);
+		}
+	}
+	Ok(false)
 }
 
 #[derive(Default)]
 struct AirplaneMode;
 
 impl SettingsGroup for AirplaneMode {
-	fn title(&self) -> &'static str {
-		"Airplane Mode"
+	fn title(&self) -> String {
+		tr("Airplane Mode")
 	}
 
-	fn keywords(&self) -> &'static [&'static str] {
-		&["airplane", "disable", "turn off"]
+	fn keywords(&self) -> Vec<String> {
+		keywords("airplane;disable;turn off")
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		vec![Row::new(tr("Airplane Mode"), Control::Switch)
+			.description(tr("Disables Wi-Fi and mobile broadband"))]
 	}
 
 	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
 		view! {
 			entry = LabeledItem {
-				set_title: "Airplane Mode",
-				set_description: "Disables Wi-Fi, Bluetooth, and mobile broadband",
+				set_title: &tr("Airplane Mode"),
+				set_description: &tr("Disables Wi-Fi and mobile broadband"),
 				set_child: checkbox = &Switch {
 					set_valign: Align::Center
 				}
 			}
 		}
+		a11y::set_label(&checkbox, &tr("Airplane Mode"));
+		bind_radios(&checkbox, &["WirelessEnabled", "WwanEnabled"], true);
 		target.container_add(&entry);
 	}
 }
@@ -56,24 +178,30 @@ impl SettingsGroup for AirplaneMode {
 struct Wifi;
 
 impl SettingsGroup for Wifi {
-	fn title(&self) -> &'static str {
-		"Wi-Fi"
+	fn title(&self) -> String {
+		tr("Wi-Fi")
 	}
 
-	fn keywords(&self) -> &'static [&'static str] {
-		&["wifi", "wi-fi", "wireless", "disable", "turn off"]
+	fn keywords(&self) -> Vec<String> {
+		keywords("wifi;wi-fi;wireless;disable;turn off")
+	}
+
+	fn rows(&self) -> Vec<Row> {
+		vec![Row::new(tr("Wi-Fi"), Control::Switch).description(tr("Disables all Wi-Fi functions"))]
 	}
 
 	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
 		view! {
 			entry = LabeledItem {
-				set_title: "Wi-Fi",
-				set_description: "Disables all Wi-Fi functions",
+				set_title: &tr("Wi-Fi"),
+				set_description: &tr("Disables all Wi-Fi functions"),
 				set_child: checkbox = &Switch {
 					set_valign: Align::Center
 				}
 			}
 		}
+		a11y::set_label(&checkbox, &tr("Wi-Fi"));
+		bind_radios(&checkbox, &["WirelessEnabled"], false);
 		target.container_add(&entry);
 	}
 }
@@ -81,48 +209,137 @@ impl SettingsGroup for Wifi {
 #[derive(Default)]
 struct AdditionalNetworkSettings;
 
+/// A network that doesn't broadcast its name, which was entered in the hidden network popup.
+struct HiddenNetwork {
+	ssid: String,
+	password: String,
+}
+
 impl AdditionalNetworkSettings {
-	pub fn create_hidden_network_popup() -> gtk4::Box {
+	/// Asks for the name and password of a hidden network, then connects to it.
+	async fn connect_to_hidden_network(ui: Rc<SettingsGui>) {
 		view! {
-			base = gtk4::Box {
+			form = gtk4::Box {
 				set_orientation: Orientation::Vertical,
-				append: label = &Label {
-					set_markup: "<b>Hidden Network</b>",
-					set_halign: Align::Center
+				set_spacing: 8,
+				append: ssid = &Entry {
+					set_placeholder_text: Some(&tr("Network Name")),
+					set_activates_default: true
 				},
+				append: password = &PasswordEntry {
+					set_placeholder_text: Some(&tr("Password")),
+					set_show_peek_icon: true,
+					set_activates_default: true
+				}
+			}
+		}
+		// Placeholders aren't read out by every screen reader.
+		a11y::set_label(&ssid, &tr("Network Name"));
+		a11y::set_label(&password, &tr("Password"));
+		let dialog = PopupDialog::new(&tr("Hidden Network"), &form).default_button(
+			&tr("Connect"),
+			glib::clone!(@strong ssid, @strong password => move || HiddenNetwork {
+				ssid: ssid.text().trim().to_string(),
+				password: password.text().to_string(),
+			}),
+		);
+		let network = match ui.popup.run(dialog).await {
+			Some(network) if !network.ssid.is_empty() => network,
+			Some(_) => {
+				ui.notifications
+					.toast(&tr("Enter the name of the network to connect to it"), None);
+				return;
+			}
+			None => return,
+		};
+		let ssid = network.ssid.clone();
+		match crate::task::spawn(Self::add_and_activate(network)).await {
+			Ok(Ok(())) => {
+				ui.notifications
+					.toast(&tr("Connecting to {ssid}").replace("{ssid}", &ssid), None);
 			}
+			Ok(Err(err)) => {
+				error!(?err, %ssid, "Failed to connect to hidden network");
+				ui.notifications.toast(
+					&tr("Couldn't connect to {ssid}: {error}")
+						.replace("{ssid}", &ssid)
+						.replace("{error}", &format!("{:#}", err)),
+					None,
+				);
+			}
+			Err(err) => error!(?err, "Hidden network task failed"),
 		}
-		base
+	}
+
+	/// Asks NetworkManager to add a connection for a hidden network and connect to it.
+	async fn add_and_activate(network: HiddenNetwork) -> anyhow::Result<()> {
+		let connection = Connection::system().await?;
+		let proxy = Proxy::new(
+			&connection,
+			"org.freedesktop.NetworkManager",
+			"/org/freedesktop/NetworkManager",
+			"org.freedesktop.NetworkManager",
+		)
+		.await?;
+		let mut settings = HashMap::<&str, HashMap<&str, Value>>::new();
+		settings.insert(
+			"connection",
+			HashMap::from([
+				("id", Value::from(network.ssid.as_str())),
+				("type", Value::from("802-11-wireless")),
+			]),
+		);
+		settings.insert(
+			"802-11-wireless",
+			HashMap::from([
+				("ssid", Value::from(network.ssid.as_bytes())),
+				("hidden", Value::from(true)),
+			]),
+		);
+		if !network.password.is_empty() {
+			settings.insert(
+				"802-11-wireless-security",
+				HashMap::from([
+					("key-mgmt", Value::from("wpa-psk")),
+					("psk", Value::from(network.password.as_str())),
+				]),
+			);
+		}
+		// NetworkManager picks the device and access point itself when given "/".
+		let any = ObjectPath::try_from("/")?;
+		let _: (OwnedObjectPath, OwnedObjectPath) = proxy
+			.call("AddAndActivateConnection", &(settings, &any, &any))
+			.await?;
+		Ok(())
 	}
 }
 
 impl SettingsGroup for AdditionalNetworkSettings {
-	fn title(&self) -> &'static str {
-		"Additional Network Settings"
+	fn title(&self) -> String {
+		tr("Additional Network Settings")
+	}
+
+	fn keywords(&self) -> Vec<String> {
+		keywords("wifi;wi-fi;wireless;hidden;network")
 	}
 
-	fn keywords(&self) -> &'static [&'static str] {
-		&[
-			"wifi", "wi-fi", "wireless", "hotspot", "hidden", "network", "tether", "hot-spot",
-			"hot spot",
-		]
+	fn rows(&self) -> Vec<Row> {
+		let label = tr("Connect to Hidden Networks");
+		let control = Control::Button {
+			label: label.clone(),
+			page: None,
+		};
+		vec![Row::new(label, control)]
 	}
 
 	fn layout(&self, target: &gtk4::Box, ui: Rc<SettingsGui>) {
 		let button = Button::builder()
-			.label("Wi-Fi Hotspot")
-			.css_classes(vec!["settings-button".into()])
-			.build();
-		target.append(&button);
-		let button = Button::builder()
-			.label("Connect to Hidden Networks")
+			.label(&tr("Connect to Hidden Networks"))
 			.css_classes(vec!["settings-button".into()])
 			.build();
 		button.connect_clicked(glib::clone!(@strong ui => move |_| {
-			ui.popup.pop_up("hidden-net");
+			crate::task::spawn_local(Self::connect_to_hidden_network(ui.clone()));
 		}));
 		target.append(&button);
-		ui.popup
-			.add_overlay("hidden-net", Self::create_hidden_network_popup);
 	}
 }
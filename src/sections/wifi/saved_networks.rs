@@ -1,38 +1,60 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::{sections::SettingsGroup, ui::SettingsGui};
+use crate::{
+	a11y,
+	i18n::{keywords, tr},
+	sections::SettingsGroup,
+	task,
+	ui::SettingsGui,
+};
+use anyhow::Context;
 use cosmic_dbus_networkmanager::settings::{connection::Settings, NetworkManagerSettings};
-use gtk4::{prelude::*, Button, Image, Label, Orientation};
+use gtk4::{glib, prelude::*, Button, Image, Label, Orientation};
 use std::rc::Rc;
-use tokio::sync::mpsc::UnboundedSender;
 use zbus::Connection;
 
 #[derive(Default)]
 pub struct SavedNetworks;
 
-impl SavedNetworks {
-	async fn get_connections(tx: UnboundedSender<Vec<Settings>>) {
-		let sys_conn = match Connection::system().await {
-			Ok(conn) => conn,
-			Err(err) => {
-				error!(%err, "Failed to connect to system dbus session");
-				return;
-			}
-		};
-		let settings = match NetworkManagerSettings::new(&sys_conn).await {
-			Ok(p) => p,
-			Err(err) => {
-				error!(%err, "Failed to set up connection to NetworkManager settings");
-				return;
-			}
-		};
-		let connections = match settings.list_connections().await {
-			Ok(connections) => connections,
-			Err(err) => {
-				error!(%err, "Failed to get list of connections from NetworkManager");
-				return;
-			}
+/// A connection that NetworkManager has saved.
+struct SavedNetwork {
+	name: String,
+	icon: &'static str,
+}
+
+impl SavedNetwork {
+	/// Describes a saved connection, unless it has no name to show.
+	fn new(settings: Settings) -> Option<Self> {
+		let icon = if settings.ethernet.is_some() {
+			"network-wired"
+		} else if settings.wifi.is_some() {
+			"network-wireless"
+		} else if settings.bluetooth.is_some() {
+			"bluetooth-active"
+		} else if settings.proxy.is_some() {
+			"network-vpn"
+		} else {
+			"dialog-question"
 		};
+		Some(Self {
+			name: settings.connection?.id?,
+			icon,
+		})
+	}
+}
+
+impl SavedNetworks {
+	async fn get_connections() -> anyhow::Result<Vec<SavedNetwork>> {
+		let sys_conn = Connection::system()
+			.await
+			.context("failed to connect to the system D-Bus")?;
+		let settings = NetworkManagerSettings::new(&sys_conn)
+			.await
+			.context("failed to connect to NetworkManager settings")?;
+		let connections = settings
+			.list_connections()
+			.await
+			.context("failed to get the list of connections from NetworkManager")?;
 		let mut out = Vec::with_capacity(connections.len());
 		for connection in connections {
 			let filename = connection
@@ -47,85 +69,69 @@ impl SavedNetworks {
 				}
 			};
 			debug!(%filename, ?settings, "Got settings for connection");
-			out.push(settings);
+			match SavedNetwork::new(settings) {
+				Some(network) => out.push(network),
+				None => warn!(%filename, "Skipping connection without a name"),
+			}
 		}
-		if let Err(err) = tx.send(out) {
-			error!(%err, "Failed to send settings to main thread");
+		Ok(out)
+	}
+
+	fn show_connections(target: &gtk4::Box, networks: Vec<SavedNetwork>) {
+		for SavedNetwork { name, icon } in networks {
+			view! {
+				outer_box = gtk4::Box {
+					set_orientation: Orientation::Horizontal,
+					set_margin_start: 24,
+					set_margin_end: 24,
+					set_margin_top: 8,
+					set_margin_bottom: 8,
+					append: connect_button = &Button {
+						add_css_class: "settings-button",
+						set_hexpand: true,
+						set_child: inner_box = Some(&gtk4::Box) {
+							set_orientation: Orientation::Horizontal,
+							set_spacing: 16,
+							append: image = &Image::from_icon_name(icon) {},
+							append: label = &Label::new(Some(&name)) {}
+						}
+					},
+					append: settings_button = &Button {
+						add_css_class: "settings-button",
+						set_icon_name: "emblem-system-symbolic",
+					}
+				}
+			}
+			a11y::set_label(&connect_button, &name);
+			a11y::set_label(
+				&settings_button,
+				&tr("Details of {network}").replace("{network}", &name),
+			);
+			target.append(&outer_box);
 		}
 	}
 }
 
 impl SettingsGroup for SavedNetworks {
-	fn title(&self) -> &'static str {
-		"Saved Networks"
+	fn title(&self) -> String {
+		tr("Saved Networks")
 	}
 
-	fn keywords(&self) -> &'static [&'static str] {
-		&[
-			"network",
-			"wifi",
-			"wi fi",
-			"wi-fi",
-			"saved",
-			"ethernet",
-			"connection",
-		]
+	fn keywords(&self) -> Vec<String> {
+		keywords("network;wifi;wi fi;wi-fi;saved;ethernet;connection")
 	}
 
 	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
-		let (net_tx, mut net_rx) = tokio::sync::mpsc::unbounded_channel();
-		crate::task::spawn(async move {
-			Self::get_connections(net_tx).await;
-		});
-
-		let target = target.downgrade();
-		crate::task::spawn_local(async move {
-			let settings = match net_rx.recv().await {
-				Some(settings) => settings,
-				None => return, //TODO err msg
-			};
-			let target = match target.upgrade() {
-				Some(target) => target,
-				None => return,
-			};
-			for setting in settings {
-				let connection_settings = setting.connection.unwrap();
-				let icon_name = if setting.ethernet.is_some() {
-					"network-wired"
-				} else if setting.wifi.is_some() {
-					"network-wireless"
-				} else if setting.bluetooth.is_some() {
-					"bluetooth-active"
-				} else if setting.proxy.is_some() {
-					"network-vpn"
-				} else {
-					"dialog-question"
-				};
-				view! {
-					outer_box = gtk4::Box {
-						set_orientation: Orientation::Horizontal,
-						set_margin_start: 24,
-						set_margin_end: 24,
-						set_margin_top: 8,
-						set_margin_bottom: 8,
-						append: connect_button = &Button {
-							add_css_class: "settings-button",
-							set_hexpand: true,
-							set_child: inner_box = Some(&gtk4::Box) {
-								set_orientation: Orientation::Horizontal,
-								set_spacing: 16,
-								append: icon = &Image::from_icon_name(icon_name) {},
-								append: label = &Label::new(Some(&connection_settings.id.unwrap())) {}
-							}
-						},
-						append: settings_button = &Button {
-							add_css_class: "settings-button",
-							set_icon_name: "emblem-system-symbolic",
-						}
-					}
-				}
-				target.append(&outer_box);
-			}
+		let net_tx = task::ui_channel(
+			target,
+			glib::clone!(@weak target => move |networks: Vec<SavedNetwork>| {
+				Self::show_connections(&target, networks);
+			}),
+		);
+		task::spawn_scoped(target, async move {
+			// The group was destroyed if this fails, which cancels this task anyway.
+			let _ = net_tx.send(Self::get_connections().await?);
+			Ok(())
 		});
 	}
 }
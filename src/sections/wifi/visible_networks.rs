@@ -1,57 +1,56 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::{sections::SettingsGroup, ui::SettingsGui};
+use crate::{
+	a11y,
+	i18n::{keywords, tr, tr_n},
+	sections::SettingsGroup,
+	task,
+	ui::{
+		notifications::{Action, Banner},
+		popup::PopupDialog,
+		visibility, SettingsGui,
+	},
+};
+use anyhow::{anyhow, Context};
 use cosmic_dbus_networkmanager::{
 	device::SpecificDevice, interface::enums::ApSecurityFlags, nm::NetworkManager,
 };
 use futures::{StreamExt, TryFutureExt};
-use gtk4::{
-	glib, prelude::*, Align, Button, Dialog, HeaderBar, Image, Label, Orientation, Spinner,
-};
+use gtk4::{glib, prelude::*, Align, Button, Image, Label, Orientation, Spinner};
 use itertools::Itertools;
 use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
 use slotmap::{DefaultKey, SlotMap};
-use std::{
-	rc::Rc,
-	sync::{
-		atomic::{AtomicBool, Ordering},
-		Arc,
-	},
-};
-use tokio::sync::mpsc::UnboundedSender;
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+use tokio::sync::Notify;
 use zbus::Connection;
 
-pub struct VisibleNetworks {
-	spinner: Spinner,
-}
+#[derive(Default)]
+pub struct VisibleNetworks;
 
-impl Default for VisibleNetworks {
-	fn default() -> Self {
-		view! {
-			spinner = Spinner {
-				set_margin_top: 8,
-				set_margin_bottom: 8,
-				set_margin_start: 8,
-				set_margin_end: 8,
-				set_halign: Align::Center,
-				set_spinning: true
-			}
-		}
-		Self { spinner }
-	}
+/// The access points found by scanning with every wireless device.
+struct Scan {
+	access_points: SlotMap<DefaultKey, AccessPoint>,
+	/// Why scanning failed with each device that couldn't scan.
+	failures: Vec<anyhow::Error>,
 }
 
 impl VisibleNetworks {
 	fn handle_access_point(
 		target: &gtk4::Box,
-		tx: &UnboundedSender<NetworksEvent>,
-		aps: &SlotMap<DefaultKey, AccessPoint>,
+		ui: &Rc<SettingsGui>,
+		aps: &Rc<RefCell<SlotMap<DefaultKey, AccessPoint>>>,
+		status: &Label,
 	) {
-		while let Some(widget) = target.first_child().as_ref() {
-			target.remove(widget);
+		let mut child = target.first_child();
+		while let Some(widget) = child {
+			child = widget.next_sibling();
+			// The status is kept, so that screen readers notice when its text changes.
+			if widget != *status {
+				target.remove(&widget);
+			}
 		}
 
-		for (id, ap) in aps.iter() {
+		for (id, ap) in aps.borrow().iter() {
 			// dbg!(&ap);
 			view! {
 				outer_box = gtk4::Box {
@@ -77,41 +76,82 @@ impl VisibleNetworks {
 				}
 			}
 
-			connect_button.connect_clicked(glib::clone!(@strong tx => move |_| {
-				let _ = tx.send(NetworksEvent::ConfigureDevice(id));
+			a11y::set_label(&connect_button, &ap.ssid);
+			a11y::set_label(
+				&settings_button,
+				&tr("Details of {network}").replace("{network}", &ap.ssid),
+			);
+
+			connect_button.connect_clicked(glib::clone!(@strong ui, @strong aps => move |_| {
+				if let Some(ap) = aps.borrow().get(id) {
+					Self::configure(&ui, ap);
+				}
 			}));
 
-			settings_button.connect_clicked(glib::clone!(@strong tx => move |_| {
-				let _ = tx.send(NetworksEvent::ConfigureDevice(id));
+			settings_button.connect_clicked(glib::clone!(@strong ui, @strong aps => move |_| {
+				if let Some(ap) = aps.borrow().get(id) {
+					Self::configure(&ui, ap);
+				}
 			}));
 
 			target.prepend(&outer_box);
 		}
+
+		let count = aps.borrow().len();
+		status.set_text(
+			&tr_n("{count} network found", "{count} networks found", count)
+				.replace("{count}", &count.to_string()),
+		);
 	}
 
-	async fn scan_for_devices(tx: UnboundedSender<NetworksEvent>) {
-		let sys_conn = match Connection::system().await {
-			Ok(conn) => conn,
-			Err(err) => {
-				error!(%err, "Failed to connect to system dbus session");
-				return;
-			}
-		};
-		let nm = match NetworkManager::new(&sys_conn).await {
-			Ok(p) => p,
-			Err(err) => {
-				error!(%err, "Failed to set up connection to NetworkManager dbus");
-				return;
-			}
-		};
-		let devices = match nm.devices().await {
-			Ok(d) => d,
-			Err(err) => {
-				error!(%err, "Failed to get devices from NetworkManager");
-				return;
+	fn configure(ui: &Rc<SettingsGui>, ap: &AccessPoint) {
+		debug!(?ap, "Configuring access point");
+
+		view! {
+			info_box = gtk4::Box {
+				set_orientation: Orientation::Vertical,
+				set_spacing: 8,
+				container_add: ssid_section = &LabeledItem {
+					set_title: &tr("SSID"),
+					set_child: ssid_label = &gtk4::Label::new(Some(ap.ssid.as_str())) {
+						add_css_class: "settings-entry-text"
+					}
+				},
+				container_add: bssid_section = &LabeledItem {
+					set_title: &tr("BSSID"),
+					set_child: bssid_label = &gtk4::Label::new(Some(ap.hw_address.as_str())) {
+						add_css_class: "settings-entry-text"
+					}
+				},
+				container_add: strength_section = &LabeledItem {
+					set_title: &tr("Signal Strength"),
+					set_child: strength_label = &gtk4::Label::new(Some(&format!("{}%", ap.strength))) {
+						add_css_class: "settings-entry-text"
+					}
+				},
 			}
-		};
+		}
+
+		let dialog = PopupDialog::<()>::new(&ap.ssid, &info_box);
+		let ui = ui.clone();
+		task::spawn_local(async move {
+			ui.popup.run(dialog).await;
+		});
+	}
+
+	async fn scan_for_devices() -> anyhow::Result<Scan> {
+		let sys_conn = Connection::system()
+			.await
+			.context("failed to connect to the system D-Bus")?;
+		let nm = NetworkManager::new(&sys_conn)
+			.await
+			.context("failed to connect to NetworkManager")?;
+		let devices = nm
+			.devices()
+			.await
+			.context("failed to get devices from NetworkManager")?;
 		let mut all_aps = SlotMap::new();
+		let mut failures = Vec::new();
 
 		for d in devices {
 			if let Ok(Some(SpecificDevice::Wireless(w))) = d.downcast_to_device().await {
@@ -120,8 +160,13 @@ impl VisibleNetworks {
 					.and_then(|ac| async move { ac.id().await })
 					.await
 					.unwrap_or_else(|_| "unknown".to_string());
-				if let Err(err) = w.request_scan(std::collections::HashMap::new()).await {
-					error!(%err, %id, "Wi-Fi scan failed");
+				if let Err(err) = w
+					.request_scan(std::collections::HashMap::new())
+					.await
+					.with_context(|| format!("failed to scan with '{}'", id))
+				{
+					error!(?err, "Wi-Fi scan failed");
+					failures.push(err);
 					continue;
 				};
 				let mut scan_changed = w.receive_last_scan_changed().await;
@@ -129,6 +174,7 @@ impl VisibleNetworks {
 					if let Ok(t) = t.get().await {
 						if t == -1 {
 							error!(%id, "Getting access point failed");
+							failures.push(anyhow!("scanning with '{}' failed", id));
 							continue;
 						}
 					}
@@ -144,6 +190,12 @@ impl VisibleNetworks {
 						}
 						Err(err) => {
 							error!(%err, %id, "Getting access points failed");
+							failures.push(
+								anyhow::Error::from(err).context(format!(
+									"failed to get the networks found by '{}'",
+									id
+								)),
+							);
 							continue;
 						}
 					};
@@ -151,17 +203,43 @@ impl VisibleNetworks {
 			}
 		}
 
-		if let Err(err) = tx.send(NetworksEvent::ApList(all_aps)) {
-			error!(%err, "failed to send AP list");
-		}
+		Ok(Scan {
+			access_points: all_aps,
+			failures,
+		})
+	}
+
+	/// Describes why scanning failed, for the banner above the page.
+	fn describe_failure(scan: &anyhow::Result<Scan>) -> Option<String> {
+		let (text, error) = match scan {
+			Ok(Scan { failures, .. }) if failures.is_empty() => return None,
+			Ok(Scan { failures, .. }) => (
+				tr("Wi-Fi scan failed: {error}"),
+				failures.iter().map(|err| format!("{:#}", err)).join("; "),
+			),
+			Err(err) if is_not_running(err) => (
+				tr("NetworkManager is not running: {error}"),
+				format!("{:#}", err),
+			),
+			Err(err) => (
+				tr("Couldn't scan for Wi-Fi networks: {error}"),
+				format!("{:#}", err),
+			),
+		};
+		Some(text.replace("{error}", &error))
 	}
 }
 
-#[derive(Debug)]
-enum NetworksEvent {
-	ApList(SlotMap<DefaultKey, AccessPoint>),
-	ConfigureDevice(DefaultKey),
-	Quit,
+/// Whether an error was caused by NetworkManager not being on the system bus.
+fn is_not_running(err: &anyhow::Error) -> bool {
+	err.chain()
+		.any(|cause| match cause.downcast_ref::<zbus::Error>() {
+			Some(zbus::Error::MethodError(name, ..)) => {
+				name.as_str() == "org.freedesktop.DBus.Error.ServiceUnknown"
+			}
+			Some(zbus::Error::FDO(err)) => matches!(**err, zbus::fdo::Error::ServiceUnknown(_)),
+			_ => false,
+		})
 }
 
 #[derive(Debug)]
@@ -210,101 +288,80 @@ impl AccessPoint {
 }
 
 impl SettingsGroup for VisibleNetworks {
-	fn title(&self) -> &'static str {
-		"Visible Networks"
+	fn title(&self) -> String {
+		tr("Visible Networks")
 	}
 
-	fn keywords(&self) -> &'static [&'static str] {
-		&["wifi", "wi-fi", "connect", "ssid"]
+	fn keywords(&self) -> Vec<String> {
+		keywords("wifi;wi-fi;connect;ssid")
 	}
 
-	fn layout(&self, target: &gtk4::Box, _ui: Rc<SettingsGui>) {
-		target.append(&self.spinner);
-
-		let (net_tx, mut net_rx) = tokio::sync::mpsc::unbounded_channel();
-
-		target.connect_destroy(glib::clone!(@strong net_tx => move |_| {
-			let _ = net_tx.send(NetworksEvent::Quit);
-		}));
-
-		let cancel = Arc::new(AtomicBool::new(false));
-
-		crate::task::spawn({
-			let cancel = cancel.clone();
-			let tx = net_tx.clone();
-			async move {
-				loop {
-					if cancel.load(Ordering::Relaxed) {
-						warn!("Stopped network scanning");
-						return;
-					}
-
-					Self::scan_for_devices(tx.clone()).await;
-					tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-				}
+	fn layout(&self, target: &gtk4::Box, ui: Rc<SettingsGui>) {
+		view! {
+			spinner = Spinner {
+				set_margin_top: 8,
+				set_margin_bottom: 8,
+				set_margin_start: 8,
+				set_margin_end: 8,
+				set_halign: Align::Center,
+				set_spinning: true
 			}
-		});
-
-		let target = target.downgrade();
-		crate::task::spawn_local(async move {
-			let mut aps = SlotMap::new();
-
-			while let Some(event) = net_rx.recv().await {
-				match event {
-					NetworksEvent::ApList(update) => {
-						if let Some(target) = target.upgrade() {
-							Self::handle_access_point(&target, &net_tx, &update);
-						}
+		}
+		a11y::set_label(&spinner, &tr("Searching for networks"));
+		target.append(&spinner);
+		let status = a11y::status_label();
+		status.set_margin_start(24);
+		status.set_margin_end(24);
+		status.set_margin_bottom(8);
+		status.add_css_class("settings-entry-text");
+		target.append(&status);
 
-						aps = update;
+		let aps = Rc::new(RefCell::new(SlotMap::new()));
+		let rescan = Arc::new(Notify::new());
+		// The banner that is shown, along with its text.
+		let banner = RefCell::new(None::<(String, Banner)>);
+		let net_tx = task::ui_channel(
+			target,
+			glib::clone!(@weak target, @weak status, @strong aps, @strong rescan => move |scan: anyhow::Result<Scan>| {
+				let failure = Self::describe_failure(&scan);
+				match scan {
+					Ok(scan) => {
+						*aps.borrow_mut() = scan.access_points;
+						Self::handle_access_point(&target, &ui, &aps, &status);
 					}
+					Err(err) => task::report_error(&target, &err),
+				}
+				if banner.borrow().as_ref().map(|(text, _)| text) == failure.as_ref() {
+					return;
+				}
+				if let Some((_, banner)) = banner.take() {
+					banner.dismiss();
+				}
+				if let Some(text) = failure {
+					let retry = Action::new(&tr("Retry"), glib::clone!(@strong rescan => move || {
+						rescan.notify_one();
+					}));
+					let shown = ui.notifications.banner(&target, &text, Some(retry));
+					banner.replace(Some((text, shown)));
+				}
+			}),
+		);
 
-					NetworksEvent::ConfigureDevice(device) => {
-						if let Some(ap) = aps.get(device) {
-							debug!(?ap, "Configuring access point");
-
-							view! {
-								dialog = Dialog {
-									set_titlebar: header = Some(&HeaderBar) {
-										add_css_class: "titlebar"
-									},
-									set_child: info_box = Some(&gtk4::Box) {
-										set_orientation: Orientation::Vertical,
-										set_spacing: 8,
-										container_add: ssid_section = &LabeledItem {
-											set_title: "SSID",
-											set_child: ssid_label = &gtk4::Label::new(Some(ap.ssid.as_str())) {
-												add_css_class: "settings-entry-text"
-											}
-										},
-										container_add: bssid_section = &LabeledItem {
-											set_title: "BSSID",
-											set_child: bssid_label = &gtk4::Label::new(Some(ap.hw_address.as_str())) {
-												add_css_class: "settings-entry-text"
-											}
-										},
-										container_add: strength_section = &LabeledItem {
-											set_title: "Signal Strength",
-											set_child: strength_label = &gtk4::Label::new(Some(&format!("{}%", ap.strength))) {
-												add_css_class: "settings-entry-text"
-											}
-										},
-									}
-								}
-							}
-
-							crate::task::spawn_local(async move {
-								dialog.run_future().await;
-								dialog.close();
-							});
-						}
+		let mut visible = visibility::track(target);
+		task::spawn_scoped(target, async move {
+			loop {
+				// Scanning is paused while the page is hidden or the window is minimized.
+				while !*visible.borrow() {
+					if visible.changed().await.is_err() {
+						return Ok(());
 					}
+				}
 
-					NetworksEvent::Quit => {
-						warn!("Stopping network scanning");
-						cancel.store(true, Ordering::SeqCst);
-						break;
-					}
+				// The group was destroyed if this fails, which cancels scanning anyway.
+				let _ = net_tx.send(Self::scan_for_devices().await);
+				tokio::select! {
+					_ = tokio::time::sleep(std::time::Duration::from_secs(5)) => {}
+					_ = rescan.notified() => {}
 				}
 			}
 		});
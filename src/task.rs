@@ -1,4 +1,10 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use anyhow::Result;
+use futures::future::{abortable, Aborted};
+use gtk4::{prelude::*, Align, Label, Widget};
 use std::future::Future;
+use tokio::sync::mpsc::{self, UnboundedSender};
 
 pub fn spawn<O, F>(future: F) -> tokio::task::JoinHandle<O>
 where
@@ -11,3 +17,90 @@ where
 pub fn spawn_local<F: Future<Output = ()> + 'static>(future: F) {
 	gtk4::glib::MainContext::default().spawn_local(future);
 }
+
+/// Spawns a future on the runtime that is cancelled when `owner` is destroyed.
+///
+/// If the future fails, its error is shown in the settings group containing `owner`.
+pub fn spawn_scoped<W, F>(owner: &W, future: F)
+where
+	W: IsA<Widget>,
+	F: Future<Output = Result<()>> + Send + 'static,
+{
+	let (future, handle) = abortable(future);
+	owner.connect_destroy(move |_| handle.abort());
+	let task = spawn(future);
+	let owner = owner.downgrade();
+	spawn_local(async move {
+		let result = match task.await {
+			Ok(Ok(result)) => result,
+			Ok(Err(Aborted)) => return,
+			Err(err) => Err(err.into()),
+		};
+		if let (Err(err), Some(owner)) = (result, owner.upgrade()) {
+			report_error(&owner, &err);
+		}
+	});
+}
+
+/// Spawns a future on the main thread that is cancelled when `owner` is destroyed.
+///
+/// If the future fails, its error is shown in the settings group containing `owner`.
+pub fn spawn_local_scoped<W, F>(owner: &W, future: F)
+where
+	W: IsA<Widget>,
+	F: Future<Output = Result<()>> + 'static,
+{
+	let (future, handle) = abortable(future);
+	owner.connect_destroy(move |_| handle.abort());
+	let owner = owner.downgrade();
+	spawn_local(async move {
+		if let (Ok(Err(err)), Some(owner)) = (future.await, owner.upgrade()) {
+			report_error(&owner, &err);
+		}
+	});
+}
+
+/// Returns a sender that background tasks can use to send messages to the UI.
+///
+/// `f` is called on the main thread with every message, until `owner` is destroyed.
+pub fn ui_channel<W, T, F>(owner: &W, f: F) -> UnboundedSender<T>
+where
+	W: IsA<Widget>,
+	T: 'static,
+	F: Fn(T) + 'static,
+{
+	let (tx, mut rx) = mpsc::unbounded_channel();
+	spawn_local_scoped(owner, async move {
+		while let Some(message) = rx.recv().await {
+			f(message);
+		}
+		Ok(())
+	});
+	tx
+}
+
+/// Logs an error, and shows it at the bottom of the settings group containing `widget`,
+/// replacing any error that it already shows.
+pub fn report_error(widget: &impl IsA<Widget>, err: &anyhow::Error) {
+	error!(?err, "Settings task failed");
+	let card = std::iter::successors(Some(widget.as_ref().clone()), |widget| widget.parent())
+		.find(|widget| widget.has_css_class("settings-group"))
+		.and_then(|card| card.downcast::<gtk4::Box>().ok());
+	let card = match card {
+		Some(card) => card,
+		None => return,
+	};
+	let existing = std::iter::successors(card.first_child(), |child| child.next_sibling())
+		.filter_map(|child| child.downcast::<Label>().ok())
+		.find(|label| label.has_css_class("settings-group-error"));
+	let label = existing.unwrap_or_else(|| {
+		let label = Label::builder()
+			.css_classes(vec!["settings-group-error".into()])
+			.halign(Align::Start)
+			.wrap(true)
+			.build();
+		card.append(&label);
+		label
+	});
+	label.set_text(&format!("{:#}", err));
+}
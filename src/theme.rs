@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! The app's stylesheet, which is built from `scss/` in a variant for each color scheme, and
+//! switched whenever the system's color scheme changes.
+//!
+//! With the `live-reload` feature, the stylesheet is instead compiled from the `scss/`
+//! directory of the source tree at runtime, and recompiled whenever a file in it changes.
+
+#[cfg(feature = "live-reload")]
+mod compile;
+#[cfg(feature = "live-reload")]
+mod live;
+
+use futures::StreamExt;
+use gtk4::{gdk::Display, CssProvider, StyleContext, STYLE_PROVIDER_PRIORITY_APPLICATION};
+use std::{cell::Cell, rc::Rc};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use zbus::{Connection, Proxy};
+use zvariant::{OwnedValue, Value};
+
+/// Overrides the system's color scheme with a variant's name, such as `light`.
+const VARIANT_ENV: &str = "COSMIC_SETTINGS_THEME";
+
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const A11Y_NAMESPACE: &str = "org.gnome.desktop.a11y.interface";
+
+/// A variant of the stylesheet, each of which is compiled with its own palette from `scss/`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variant {
+	Light,
+	Dark,
+	HighContrast,
+}
+
+impl Default for Variant {
+	/// The app is dark unless the system prefers otherwise.
+	fn default() -> Self {
+		Self::Dark
+	}
+}
+
+impl Variant {
+	/// The name of the variant's palette, such as `dark` for `_palette-dark.scss`.
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Light => "light",
+			Self::Dark => "dark",
+			Self::HighContrast => "high-contrast",
+		}
+	}
+
+	fn from_name(name: &str) -> Option<Self> {
+		[Self::Light, Self::Dark, Self::HighContrast]
+			.into_iter()
+			.find(|variant| variant.name() == name)
+	}
+
+	/// The stylesheet built into the binary.
+	fn css(self) -> &'static str {
+		match self {
+			Self::Light => include_str!(concat!(env!("OUT_DIR"), "/style-light.css")),
+			Self::Dark => include_str!(concat!(env!("OUT_DIR"), "/style-dark.css")),
+			Self::HighContrast => {
+				include_str!(concat!(env!("OUT_DIR"), "/style-high-contrast.css"))
+			}
+		}
+	}
+}
+
+/// The stylesheet applied to every window of the app.
+pub struct Theme {
+	provider: CssProvider,
+	variant: Cell<Variant>,
+}
+
+impl Theme {
+	/// Styles the display's windows, and keeps the variant in line with the system's color
+	/// scheme, unless it's overridden with `COSMIC_SETTINGS_THEME`.
+	pub fn setup(display: &Display) -> Rc<Self> {
+		let overridden = std::env::var(VARIANT_ENV).ok().and_then(|name| {
+			let variant = Variant::from_name(&name);
+			if variant.is_none() {
+				warn!(%name, "Unknown theme variant");
+			}
+			variant
+		});
+		let variant = overridden.unwrap_or_default();
+		let theme = Rc::new(Self {
+			provider: CssProvider::new(),
+			variant: Cell::new(variant),
+		});
+		theme.provider.load_from_data(variant.css().as_bytes());
+		StyleContext::add_provider_for_display(
+			display,
+			&theme.provider,
+			STYLE_PROVIDER_PRIORITY_APPLICATION,
+		);
+		Self::style_builtin_widgets(variant);
+		#[cfg(feature = "live-reload")]
+		{
+			theme.reload();
+			live::watch(theme.clone());
+		}
+		if overridden.is_some() {
+			return theme;
+		}
+
+		let (tx, mut rx) = mpsc::unbounded_channel();
+		crate::task::spawn(async move {
+			if let Err(err) = watch_color_scheme(tx).await {
+				warn!(%err, "Failed to read the system color scheme");
+			}
+		});
+		// The theme lasts as long as the app, so the loop holds on to it rather than relying on
+		// callers to keep it alive.
+		let listener = theme.clone();
+		crate::task::spawn_local(async move {
+			while let Some(variant) = rx.recv().await {
+				listener.set_variant(variant);
+			}
+		});
+		theme
+	}
+
+	/// Switches to another variant of the stylesheet.
+	pub fn set_variant(&self, variant: Variant) {
+		if self.variant.replace(variant) == variant {
+			return;
+		}
+		debug!(variant = variant.name(), "Switching theme variant");
+		Self::style_builtin_widgets(variant);
+		self.reload();
+	}
+
+	/// Loads the stylesheet of the current variant again.
+	fn reload(&self) {
+		let variant = self.variant.get();
+		#[cfg(feature = "live-reload")]
+		match live::compile(variant) {
+			Ok(css) => self.provider.load_from_data(css.as_bytes()),
+			// The last stylesheet that compiled is kept until the error is fixed.
+			Err(err) => error!(%err, variant = variant.name(), "Failed to compile stylesheet"),
+		}
+		#[cfg(not(feature = "live-reload"))]
+		self.provider.load_from_data(variant.css().as_bytes());
+	}
+
+	/// Makes the widgets that are styled by GTK, rather than by our stylesheet, match the
+	/// variant.
+	fn style_builtin_widgets(variant: Variant) {
+		if let Some(settings) = gtk4::Settings::default() {
+			settings.set_gtk_application_prefer_dark_theme(variant != Variant::Light);
+		}
+	}
+}
+
+/// The system's appearance preferences, as reported by the settings portal.
+#[derive(Default)]
+struct Appearance {
+	/// 0 for no preference, 1 to prefer dark and 2 to prefer light.
+	color_scheme: u32,
+	high_contrast: bool,
+}
+
+impl Appearance {
+	fn variant(&self) -> Variant {
+		match (self.high_contrast, self.color_scheme) {
+			(true, _) => Variant::HighContrast,
+			(false, 2) => Variant::Light,
+			(false, _) => Variant::Dark,
+		}
+	}
+
+	/// Applies a setting that changed, returning whether it's one of the preferences.
+	fn update(&mut self, namespace: &str, key: &str, value: &Value) -> bool {
+		match (namespace, key, unwrap_variant(value)) {
+			(APPEARANCE_NAMESPACE, "color-scheme", Value::U32(color_scheme)) => {
+				self.color_scheme = *color_scheme;
+			}
+			(A11Y_NAMESPACE, "high-contrast", Value::Bool(high_contrast)) => {
+				self.high_contrast = *high_contrast;
+			}
+			_ => return false,
+		}
+		true
+	}
+}
+
+/// Keeps `tx` up to date with the variant that matches the system's color scheme and contrast
+/// preference, from the settings portal.
+async fn watch_color_scheme(tx: UnboundedSender<Variant>) -> zbus::Result<()> {
+	let connection = Connection::session().await?;
+	let proxy = Proxy::new(
+		&connection,
+		"org.freedesktop.portal.Desktop",
+		"/org/freedesktop/portal/desktop",
+		"org.freedesktop.portal.Settings",
+	)
+	.await?;
+	let mut changes = proxy.receive_signal("SettingChanged").await?;
+	let mut appearance = Appearance::default();
+	for (namespace, key) in [
+		(APPEARANCE_NAMESPACE, "color-scheme"),
+		(A11Y_NAMESPACE, "high-contrast"),
+	] {
+		// Settings that the portal doesn't know about are left as they are.
+		if let Ok(value) = proxy
+			.call::<_, _, OwnedValue>("Read", &(namespace, key))
+			.await
+		{
+			appearance.update(namespace, key, &value);
+		}
+	}
+	loop {
+		if tx.send(appearance.variant()).is_err() {
+			// The app no longer cares.
+			return Ok(());
+		}
+		loop {
+			let message = match changes.next().await {
+				Some(message) => message,
+				None => return Ok(()),
+			};
+			let (namespace, key, value) = message.body::<(String, String, OwnedValue)>()?;
+			if appearance.update(&namespace, &key, &value) {
+				break;
+			}
+		}
+	}
+}
+
+/// Older portals return values wrapped in another variant.
+fn unwrap_variant<'a>(value: &'a Value<'a>) -> &'a Value<'a> {
+	match value {
+		Value::Value(inner) => unwrap_variant(inner),
+		value => value,
+	}
+}
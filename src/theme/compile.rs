@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Compiles the stylesheets in `scss/`. This is shared with the build script, which compiles
+//! the stylesheets built into the binary.
+
+use std::{error::Error, fs, path::Path};
+
+/// Compiles every stylesheet in `scss_dir` that isn't a partial into one stylesheet, using the
+/// colors of a variant's palette, such as `_palette-dark.scss` for `dark`.
+pub fn compile(scss_dir: &Path, variant: &str) -> Result<String, Box<dyn Error>> {
+	let mut combined_scss = vec![format!("@import \"palette-{}\";", variant)];
+	let options = grass::Options::default().load_path(scss_dir);
+	let mut paths = fs::read_dir(scss_dir)?
+		.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+		.collect::<Vec<_>>();
+	// Rules are applied in the same order every time, whatever order the files are listed in.
+	paths.sort();
+	for path in paths {
+		// Ensure path ends with .scss and doesn't start with a _
+		if path
+			.file_name()
+			.and_then(|ext| ext.to_str().map(|x| x.starts_with('_')))
+			.unwrap_or(false)
+			|| !path
+				.extension()
+				.and_then(|ext| ext.to_str().map(|x| x == "scss"))
+				.unwrap_or(false)
+		{
+			continue;
+		}
+		let file = fs::read_to_string(&path)
+			.map_err(|err| format!("failed to read '{}': {}", path.display(), err))?;
+		combined_scss.push(file);
+	}
+	Ok(grass::from_string(combined_scss.join("\n\n"), &options)?)
+}
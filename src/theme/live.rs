@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{compile, Theme, Variant};
+use gtk4::glib;
+use notify::{RecursiveMode, Watcher as _};
+use std::{error::Error, path::PathBuf, rc::Rc, time::Duration};
+use tokio::sync::mpsc;
+
+/// Editors often save a file in several writes, which are only compiled once.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The directory the stylesheets are compiled from, which is `scss/` in the source tree unless
+/// `COSMIC_SETTINGS_SCSS_DIR` is set.
+fn scss_dir() -> PathBuf {
+	std::env::var_os("COSMIC_SETTINGS_SCSS_DIR")
+		.map(PathBuf::from)
+		.unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/scss")))
+}
+
+/// Compiles the stylesheet of a variant from the files currently in the scss directory.
+pub fn compile(variant: Variant) -> Result<String, Box<dyn Error>> {
+	compile::compile(&scss_dir(), variant.name())
+}
+
+/// Reloads the theme whenever a file in the scss directory changes.
+pub fn watch(theme: Rc<Theme>) {
+	let dir = scss_dir();
+	let (tx, mut rx) = mpsc::unbounded_channel();
+	let watcher =
+		notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+			Ok(_) => {
+				let _ = tx.send(());
+			}
+			Err(err) => error!(%err, "Failed to watch scss directory"),
+		});
+	let mut watcher = match watcher {
+		Ok(watcher) => watcher,
+		Err(err) => {
+			error!(%err, "Failed to set up scss watcher");
+			return;
+		}
+	};
+	if let Err(err) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+		error!(%err, dir = %dir.display(), "Failed to watch scss directory");
+		return;
+	}
+	info!(dir = %dir.display(), "Reloading stylesheets when they change");
+	crate::task::spawn_local(async move {
+		// The watcher stops once it's dropped.
+		let _watcher = watcher;
+		while rx.recv().await.is_some() {
+			glib::timeout_future(DEBOUNCE).await;
+			while rx.try_recv().is_ok() {}
+			theme.reload();
+		}
+	});
+}
@@ -1,17 +1,28 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+pub mod adaptive;
+pub mod bundle;
 pub mod nav;
+pub mod navigation;
+pub mod notifications;
 pub mod popup;
 pub mod search;
 pub mod section;
+pub mod shortcuts;
+pub mod visibility;
 
-pub use self::{nav::SettingsNavGui, popup::PopupGui, search::SearchGui};
-use crate::widgets::ListBoxSelectionRow;
+pub use self::{
+	adaptive::AdaptiveLayout, nav::SettingsNavGui, navigation::Navigation,
+	notifications::NotificationsGui, popup::PopupGui, search::SearchGui,
+};
+use crate::{a11y, config::Config, i18n::tr, widgets::ListBoxSelectionRow};
 use gtk4::{
+	gio,
 	glib::{self, clone},
 	prelude::*,
-	ApplicationWindow, HeaderBar, Orientation, Stack,
+	ApplicationWindow, HeaderBar, MenuButton, Orientation, Stack,
 };
+use std::rc::Rc;
 
 /// A struct containing references to various base elements of the COSMIC settings GUI.
 #[derive(Clone)]
@@ -20,6 +31,8 @@ pub struct SettingsGui {
 	pub header: HeaderBar,
 	/// The box containing the header contents
 	pub header_box: gtk4::Box,
+	/// The main menu, shown from the end of the title bar
+	pub menu: gio::Menu,
 	/// The search bar of the application
 	pub search: SearchGui,
 	/// The base box that contains everything except the header.
@@ -28,8 +41,16 @@ pub struct SettingsGui {
 	pub nav: SettingsNavGui,
 	/// Various elements related to popup UIs
 	pub popup: PopupGui,
+	/// Toasts and banners that tell the user about errors and confirm changes
+	pub notifications: NotificationsGui,
 	/// The stack that contains the pages of primary content.
 	pub content: Stack,
+	/// The pages that have been shown, for going back and forward.
+	pub navigation: Navigation,
+	/// Switches to showing one pane at a time when the window is narrow.
+	pub adaptive: AdaptiveLayout,
+	/// The store that settings widgets are bound to.
+	pub config: Rc<Config>,
 }
 
 impl SettingsGui {
@@ -39,31 +60,86 @@ impl SettingsGui {
 		let search = SearchGui::new();
 		let base = Self::create_base_box();
 		let nav = SettingsNavGui::new(&header_box);
-		header_box.append(&search.bar);
 		header.pack_start(&header_box);
+		let menu = gio::Menu::new();
+		header.pack_end(&Self::create_menu_button(&menu));
 		let content = Stack::new();
 		Self::setup_row_active(&nav, &content);
+		let navigation = Navigation::new(&header_box, &nav);
+		header_box.append(&search.bar);
+		Self::setup_navigation(&nav, &content, &navigation);
 		base.append(&nav.revealer);
-		base.append(&content);
-		let popup = PopupGui::new(&base);
+		let notifications = NotificationsGui::new(&base, &content);
+		// Toasts are below popups, so that they're insensitive while a popup is shown.
+		let popup = PopupGui::new(&notifications.overlay);
+		let content_box = gtk4::Box::new(Orientation::Vertical, 0);
+		content_box.append(&notifications.banners);
+		content_box.append(&content);
+		base.append(&content_box);
+		let adaptive = AdaptiveLayout::new(
+			window,
+			&header_box,
+			&nav,
+			&navigation,
+			&content_box,
+			&content,
+		);
 		window.set_child(Some(&popup.overlay));
 		window.set_titlebar(Some(&header));
 		Self {
 			header,
 			header_box,
+			menu,
 			search,
 			base,
 			nav,
 			popup,
+			notifications,
 			content,
+			navigation,
+			adaptive,
+			config: Rc::new(Config::new()),
 		}
 	}
 
+	/// Returns the path of every page, such as `wifi` or `desktop/dock`.
+	pub fn page_paths(&self) -> Vec<String> {
+		self.nav
+			.pages
+			.borrow()
+			.iter()
+			.map(|page| page.path.clone())
+			.collect()
+	}
+
+	/// Returns the path of the page that is currently shown, or `None` if search results
+	/// are being shown instead.
+	pub fn visible_page(&self) -> Option<String> {
+		let child = self.content.visible_child_name()?;
+		self.nav
+			.pages
+			.borrow()
+			.iter()
+			.find(|page| page.child == child.as_str())
+			.map(|page| page.path.clone())
+	}
+
 	fn create_header() -> HeaderBar {
 		HeaderBar::builder()
 			.css_classes(vec!["titlebar".into()])
 			.build()
 	}
+	fn create_menu_button(menu: &gio::Menu) -> MenuButton {
+		let button = MenuButton::builder()
+			.icon_name("open-menu-symbolic")
+			.tooltip_text(&tr("Main Menu"))
+			.menu_model(menu)
+			.css_classes(vec!["nav-history-button".into()])
+			.build();
+		a11y::set_label(&button, &tr("Main Menu"));
+		button
+	}
+
 	fn create_base_box() -> gtk4::Box {
 		gtk4::Box::builder()
 			.orientation(Orientation::Horizontal)
@@ -80,6 +156,21 @@ impl SettingsGui {
 			.build()
 	}
 
+	fn setup_navigation(nav: &SettingsNavGui, content: &Stack, navigation: &Navigation) {
+		content.connect_visible_child_name_notify(
+			clone!(@strong nav.pages as pages, @strong navigation => move |content| {
+				let child = match content.visible_child_name() {
+					Some(child) => child,
+					None => return,
+				};
+				// Search results aren't a page, so they aren't recorded.
+				if let Some(page) = pages.borrow().iter().find(|page| page.child == child.as_str()) {
+					navigation.visit(&page.path);
+				}
+			}),
+		);
+	}
+
 	fn setup_row_active(nav: &SettingsNavGui, content: &Stack) {
 		nav.list.connect_row_activated(
 			clone!(@weak content, @weak nav.labels as labels, @weak nav.stack as nav_stack, @weak nav.subsection_revealer as nav_stack_revealer => move |_, row| {
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::{Navigation, SettingsNavGui};
+use crate::{a11y, i18n::tr, widgets::ListBoxSelectionRow};
+use gtk4::{
+	glib::{self, clone},
+	prelude::*,
+	ApplicationWindow, Button, Stack,
+};
+use std::{cell::Cell, rc::Rc};
+
+/// Windows narrower than this show one pane at a time instead of the nav beside the content.
+const BREAKPOINT: i32 = 720;
+
+/// The pane shown while the window is narrow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pane {
+	/// The list of sections.
+	Sections,
+	/// The list of pages in the selected section.
+	Subsections,
+	/// The content of the selected page.
+	Page,
+}
+
+/// Switches between the nav beside the content, and showing one of them at a time with a back
+/// arrow when the window is too narrow for both, such as when it's tiled on a small screen.
+#[derive(Clone)]
+pub struct AdaptiveLayout {
+	/// The arrow that goes back to the list a page was chosen from.
+	pub back_button: Button,
+	nav: SettingsNavGui,
+	navigation: Navigation,
+	/// The box containing the content stack and its banners.
+	content_pane: gtk4::Box,
+	narrow: Rc<Cell<bool>>,
+	pane: Rc<Cell<Pane>>,
+	/// Whether the nav was revealed before the window became narrow.
+	nav_revealed: Rc<Cell<bool>>,
+}
+
+impl AdaptiveLayout {
+	pub fn new(
+		window: &ApplicationWindow,
+		header_box: &gtk4::Box,
+		nav: &SettingsNavGui,
+		navigation: &Navigation,
+		content_pane: &gtk4::Box,
+		content: &Stack,
+	) -> Self {
+		let back_button = Button::builder()
+			.icon_name("go-previous-symbolic")
+			.tooltip_text(&tr("Back"))
+			.visible(false)
+			.css_classes(vec!["nav-history-button".into()])
+			.build();
+		a11y::set_label(&back_button, &tr("Back"));
+		header_box.prepend(&back_button);
+		let layout = Self {
+			back_button,
+			nav: nav.clone(),
+			navigation: navigation.clone(),
+			content_pane: content_pane.clone(),
+			narrow: Rc::default(),
+			pane: Rc::new(Cell::new(Pane::Page)),
+			nav_revealed: Rc::default(),
+		};
+		layout
+			.back_button
+			.connect_clicked(clone!(@strong layout => move |_| {
+				layout.back();
+			}));
+		nav.list
+			.connect_row_activated(clone!(@strong layout => move |_, row| {
+				let subsection = row
+					.downcast_ref::<ListBoxSelectionRow>()
+					.map_or(false, |row| row.subsection());
+				layout.show_pane(if subsection { Pane::Subsections } else { Pane::Page });
+			}));
+		// Search results and pages shown from other places, such as the history, are content too.
+		content.connect_visible_child_name_notify(clone!(@strong layout => move |_| {
+			layout.show_pane(Pane::Page);
+		}));
+		// The surface is resized when the window is tiled or maximized, unlike its default size.
+		window.connect_realize(clone!(@strong layout => move |window| {
+			window.surface().connect_layout(clone!(@strong layout => move |_, width, _| {
+				layout.set_narrow(width < BREAKPOINT);
+			}));
+		}));
+		layout
+	}
+
+	/// Shows a pane when the window is narrow, or remembers it for when it becomes narrow.
+	pub fn show_pane(&self, pane: Pane) {
+		self.pane.set(pane);
+		self.update();
+	}
+
+	/// Goes back to the list that the shown page or list of pages was chosen from.
+	pub fn back(&self) {
+		let pane = match self.pane.get() {
+			Pane::Page if self.nav.subsection_revealer.reveals_child() => Pane::Subsections,
+			Pane::Page | Pane::Subsections | Pane::Sections => Pane::Sections,
+		};
+		self.show_pane(pane);
+	}
+
+	fn set_narrow(&self, narrow: bool) {
+		if self.narrow.replace(narrow) == narrow {
+			return;
+		}
+		if narrow {
+			self.nav_revealed.set(self.nav.revealer.reveals_child());
+			self.nav.revealer.set_reveal_child(true);
+		} else {
+			self.nav.revealer.set_reveal_child(self.nav_revealed.get());
+		}
+		self.update();
+	}
+
+	fn update(&self) {
+		let narrow = self.narrow.get();
+		let pane = self.pane.get();
+		self.back_button
+			.set_visible(narrow && pane != Pane::Sections);
+		// There's no room for anything but the back arrow and the search bar.
+		self.nav.button.set_visible(!narrow);
+		self.navigation.back_button.set_visible(!narrow);
+		self.navigation.forward_button.set_visible(!narrow);
+		self.navigation.breadcrumb.set_visible(!narrow);
+
+		self.nav.revealer.set_visible(!narrow || pane != Pane::Page);
+		self.nav.list.set_visible(!narrow || pane == Pane::Sections);
+		self.nav
+			.subsection_revealer
+			.set_visible(!narrow || pane == Pane::Subsections);
+		self.content_pane.set_visible(!narrow || pane == Pane::Page);
+		// The shown list takes up the whole window.
+		self.nav.revealer.set_hexpand(narrow);
+		self.nav.list.set_hexpand(narrow);
+		self.nav.subsection_revealer.set_hexpand(narrow);
+	}
+}
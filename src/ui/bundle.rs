@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Exporting the settings of chosen sections to a bundle, and importing a bundle after showing
+//! what it would change.
+
+use super::{notifications::Action, popup::PopupDialog, SettingsGui};
+use crate::{
+	a11y,
+	config::{Bundle, Source},
+	i18n::{tr, tr_n},
+	sections,
+};
+use gtk4::{
+	gio::SimpleAction, glib::clone, prelude::*, Align, ApplicationWindow, CheckButton,
+	FileChooserAction, FileChooserNative, FileFilter, Label, Orientation, PolicyType, ResponseType,
+	ScrolledWindow,
+};
+use libcosmic_widgets::{relm4::RelmContainerExt, LabeledItem};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use tokio::sync::oneshot;
+
+/// Adds the export and import actions to the window and to the main menu.
+pub fn setup(window: &ApplicationWindow, ui: Rc<SettingsGui>) {
+	let export = SimpleAction::new("export", None);
+	export.connect_activate(clone!(@weak window, @weak ui => move |_, _| {
+		// Only the popup can be used while it's shown.
+		if ui.popup.is_running() {
+			return;
+		}
+		crate::task::spawn_local(export_bundle(window, ui));
+	}));
+	window.add_action(&export);
+
+	let import = SimpleAction::new("import", None);
+	import.connect_activate(clone!(@weak window, @weak ui => move |_, _| {
+		if ui.popup.is_running() {
+			return;
+		}
+		crate::task::spawn_local(import_bundle(window, ui));
+	}));
+	window.add_action(&import);
+
+	ui.menu
+		.append(Some(&tr("Export Settings…")), Some("win.export"));
+	ui.menu
+		.append(Some(&tr("Import Settings…")), Some("win.import"));
+}
+
+/// Asks which sections to export and where to, then saves their settings to a bundle.
+async fn export_bundle(window: ApplicationWindow, ui: Rc<SettingsGui>) {
+	let mut sections = sections::keys();
+	let list = a11y::group(&tr("Sections"));
+	list.set_spacing(8);
+	let checks = sections
+		.iter()
+		.map(|(name, _)| {
+			let check = CheckButton::builder().label(&tr(name)).active(true).build();
+			list.append(&check);
+			check
+		})
+		.collect::<Vec<_>>();
+	view! {
+		form = gtk4::Box {
+			set_orientation: Orientation::Vertical,
+			set_spacing: 16,
+			append: description = &Label {
+				set_text: &tr("Choose the sections whose settings are saved to the bundle."),
+				set_halign: Align::Start,
+				set_wrap: true
+			},
+			append: &list
+		}
+	}
+	let dialog = PopupDialog::new(&tr("Export Settings"), &form).default_button(
+		&tr("Export"),
+		clone!(@strong checks => move || {
+			checks.iter().map(CheckButton::is_active).collect::<Vec<_>>()
+		}),
+	);
+	let chosen = match ui.popup.run(dialog).await {
+		Some(chosen) => chosen,
+		None => return,
+	};
+	let mut chosen = chosen.into_iter();
+	sections.retain(|_| chosen.next().unwrap_or_default());
+	if sections.is_empty() {
+		ui.notifications
+			.toast(&tr("Choose at least one section to export"), None);
+		return;
+	}
+
+	let path = match choose_file(
+		&window,
+		FileChooserAction::Save,
+		&tr("Export Settings"),
+		&tr("Export"),
+	)
+	.await
+	{
+		Some(path) => path,
+		None => return,
+	};
+	let file = path.display().to_string();
+	match Bundle::export(&ui.config, &sections).save(&path) {
+		Ok(()) => ui.notifications.toast(
+			&tr("Exported settings to {file}").replace("{file}", &file),
+			None,
+		),
+		Err(err) => {
+			error!(?err, %file, "Failed to export settings");
+			ui.notifications.toast(
+				&tr("Couldn't export settings: {error}").replace("{error}", &format!("{:#}", err)),
+				None,
+			);
+		}
+	}
+}
+
+/// Asks for a bundle and shows how each setting would change, then applies it if confirmed.
+async fn import_bundle(window: ApplicationWindow, ui: Rc<SettingsGui>) {
+	let path = match choose_file(
+		&window,
+		FileChooserAction::Open,
+		&tr("Import Settings"),
+		&tr("Open"),
+	)
+	.await
+	{
+		Some(path) => path,
+		None => return,
+	};
+	let file = path.display().to_string();
+	let keys = sections::all_keys();
+	let import = match Bundle::load(&path).and_then(|bundle| bundle.import(&ui.config, &keys)) {
+		Ok(import) => import,
+		Err(err) => {
+			error!(?err, %file, "Failed to read settings bundle");
+			ui.notifications.toast(
+				&tr("Couldn't import {file}: {error}")
+					.replace("{file}", &file)
+					.replace("{error}", &format!("{:#}", err)),
+				None,
+			);
+			return;
+		}
+	};
+	if import.changes.is_empty() {
+		ui.notifications.toast(
+			&tr("Your settings already match {file}").replace("{file}", &file),
+			None,
+		);
+		return;
+	}
+
+	let count = import.changes.len();
+	let changes = gtk4::Box::new(Orientation::Vertical, 0);
+	for change in &import.changes {
+		let diff = format!("{} → {}", change.old, change.new);
+		view! {
+			row = LabeledItem {
+				set_title: &change.key.to_string(),
+				set_child: diff_label = &Label {
+					set_text: &diff
+				}
+			}
+		}
+		changes.container_add(&row);
+	}
+	let scroll = ScrolledWindow::builder()
+		.hscrollbar_policy(PolicyType::Never)
+		.max_content_height(320)
+		.propagate_natural_height(true)
+		.child(&changes)
+		.build();
+	a11y::set_label(&scroll, &tr("Changes"));
+	let form = gtk4::Box::new(Orientation::Vertical, 16);
+	form.append(&scroll);
+	let unknown = tr_n(
+		"{count} setting isn't known here and will be skipped",
+		"{count} settings aren't known here and will be skipped",
+		import.unknown.len(),
+	);
+	let locked = tr_n(
+		"{count} setting is locked by your administrator and will be skipped",
+		"{count} settings are locked by your administrator and will be skipped",
+		import.locked.len(),
+	);
+	for (skipped, count) in [
+		(unknown, import.unknown.len()),
+		(locked, import.locked.len()),
+	] {
+		if count == 0 {
+			continue;
+		}
+		let label = Label::builder()
+			.label(&skipped.replace("{count}", &count.to_string()))
+			.halign(Align::Start)
+			.wrap(true)
+			.build();
+		form.append(&label);
+	}
+	let apply = tr_n("Apply {count} Change", "Apply {count} Changes", count)
+		.replace("{count}", &count.to_string());
+	let dialog = PopupDialog::new(&tr("Import Settings"), &form).default_button(&apply, || ());
+	if ui.popup.run(dialog).await.is_none() {
+		return;
+	}
+
+	let old = import
+		.changes
+		.iter()
+		.map(|change| (change.key, change.old.clone()))
+		.collect::<Vec<_>>();
+	if let Err(err) = import.apply(&ui.config) {
+		error!(?err, %file, "Failed to import settings");
+		ui.notifications.toast(
+			&tr("Some settings couldn't be imported: {error}")
+				.replace("{error}", &format!("{:#}", err)),
+			None,
+		);
+		return;
+	}
+	let undo = Action::new(
+		&tr("Undo"),
+		clone!(@strong ui => move || {
+			for (key, value) in &old {
+				if let Err(err) = ui.config.set(key, value.clone(), Source::Revert) {
+					error!(?err, %key, "Failed to restore setting");
+				}
+			}
+		}),
+	);
+	ui.notifications.toast(
+		&tr_n(
+			"Imported {count} setting",
+			"Imported {count} settings",
+			count,
+		)
+		.replace("{count}", &count.to_string()),
+		Some(undo),
+	);
+}
+
+/// Asks for a bundle to open or save with the system's file chooser, returning `None` if it's
+/// cancelled.
+async fn choose_file(
+	window: &ApplicationWindow,
+	action: FileChooserAction,
+	title: &str,
+	accept: &str,
+) -> Option<PathBuf> {
+	let chooser = FileChooserNative::new(
+		Some(title),
+		Some(window),
+		action,
+		Some(accept),
+		Some(&tr("Cancel")),
+	);
+	chooser.set_modal(true);
+	if action == FileChooserAction::Save {
+		chooser.set_current_name("cosmic-settings.toml");
+	}
+	let filter = FileFilter::new();
+	filter.set_name(Some(&tr("Settings Bundles")));
+	filter.add_pattern("*.toml");
+	chooser.add_filter(&filter);
+
+	let (tx, rx) = oneshot::channel();
+	let tx = RefCell::new(Some(tx));
+	chooser.connect_response(move |chooser, response| {
+		let path = match response {
+			ResponseType::Accept => chooser.file().and_then(|file| file.path()),
+			_ => None,
+		};
+		if let Some(tx) = tx.borrow_mut().take() {
+			let _ = tx.send(path);
+		}
+	});
+	chooser.show();
+	rx.await.ok().flatten()
+}
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use crate::{a11y, i18n::tr, widgets::ListBoxSelectionRow};
 use gtk4::{
 	glib::{self, clone},
 	prelude::*,
@@ -24,6 +25,19 @@ pub struct SettingsNavGui {
 	pub labels: Rc<RefCell<Vec<Label>>>,
 	/// The stack containing the menus for subsections
 	pub stack: Stack,
+	/// Every page of content that the nav leads to.
+	pub pages: Rc<RefCell<Vec<Page>>>,
+}
+
+/// A page of content that the nav leads to.
+#[derive(Clone, Debug)]
+pub struct Page {
+	/// The path of this page, such as `wifi` or `desktop/dock`.
+	pub path: String,
+	/// The name of this page shown to the user, such as `Desktop › Dock`.
+	pub title: String,
+	/// The name of this page in the content stack, which is its path.
+	pub child: String,
 }
 
 impl SettingsNavGui {
@@ -34,10 +48,12 @@ impl SettingsNavGui {
 		let subsection_revealer = Self::create_subsection_revealer(&stack);
 		let button = Self::create_button(header_box, &revealer);
 		let list = gtk4::ListBox::new();
+		a11y::set_label(&list, &tr("Sections"));
 		nav_box.append(&list);
 		nav_box.append(&subsection_revealer);
 		revealer.set_child(Some(&nav_box));
 		let labels = Rc::new(RefCell::new(Vec::new()));
+		let pages = Rc::new(RefCell::new(Vec::new()));
 		Self {
 			revealer,
 			subsection_revealer,
@@ -46,6 +62,58 @@ impl SettingsNavGui {
 			list,
 			labels,
 			stack,
+			pages,
+		}
+	}
+
+	/// Shows the page at the given path, such as `wifi` or `desktop/dock`, as if its nav rows
+	/// had been clicked. A section with subsections shows its first page if none is given.
+	///
+	/// Returns `false` if there is no page at that path, or if it's hidden.
+	pub fn show_page(&self, path: &str) -> bool {
+		let mut segments = path.trim_matches('/').splitn(2, '/');
+		let section = segments.next().unwrap_or_default();
+		let page = segments.next();
+		let row = match find_row(&self.list, section) {
+			Some(row) => row,
+			None => return false,
+		};
+		if !row.subsection() {
+			if page.is_some() {
+				return false;
+			}
+			self.list.select_row(Some(&row));
+			row.emit_activate();
+			return true;
+		}
+		let subsection_list = match self
+			.stack
+			.child_by_name(&row.row_id())
+			.and_then(|list| list.downcast::<ListBox>().ok())
+		{
+			Some(list) => list,
+			None => return false,
+		};
+		let page_row = match page {
+			Some(page) => find_row(&subsection_list, page),
+			None => first_visible_row(&subsection_list),
+		};
+		let page_row = match page_row {
+			Some(page_row) => page_row,
+			None => return false,
+		};
+		self.list.select_row(Some(&row));
+		row.emit_activate();
+		subsection_list.select_row(Some(&page_row));
+		page_row.emit_activate();
+		true
+	}
+
+	/// Shows the first page that isn't hidden.
+	pub fn show_first_page(&self) -> bool {
+		match first_visible_row(&self.list) {
+			Some(row) => self.show_page(&row.row_id()),
+			None => false,
 		}
 	}
 
@@ -88,7 +156,7 @@ impl SettingsNavGui {
 			.margin_start(10)
 			.margin_end(10)
 			.build();
-		let button_label = gtk4::Label::new(Some("Navigation"));
+		let button_label = gtk4::Label::new(Some(&tr("Navigation")));
 		button_box.append(&button_label);
 		let button_sep = gtk4::Separator::new(Orientation::Vertical);
 		button_box.append(&button_sep);
@@ -99,6 +167,7 @@ impl SettingsNavGui {
 			.margin_top(10)
 			.css_classes(vec!["nav-button".into()])
 			.build();
+		a11y::set_label(&button, &tr("Navigation"));
 		button.connect_clicked(clone!(@weak revealer, @weak button_icon => move |_| {
 			let active = revealer.reveals_child();
 			if active {
@@ -113,3 +182,24 @@ impl SettingsNavGui {
 		button
 	}
 }
+
+/// Converts the name of a section or page into the form used in page paths.
+pub fn path_segment(name: &str) -> String {
+	name.trim().to_lowercase().replace(' ', "-")
+}
+
+/// Finds the visible row of a list whose path ends with the given segment.
+fn find_row(list: &ListBox, segment: &str) -> Option<ListBoxSelectionRow> {
+	let segment = path_segment(segment);
+	(0..)
+		.map_while(|index| list.row_at_index(index))
+		.filter_map(|row| row.downcast::<ListBoxSelectionRow>().ok())
+		.find(|row| row.is_visible() && row.row_id().rsplit('/').next() == Some(segment.as_str()))
+}
+
+fn first_visible_row(list: &ListBox) -> Option<ListBoxSelectionRow> {
+	(0..)
+		.map_while(|index| list.row_at_index(index))
+		.filter_map(|row| row.downcast::<ListBoxSelectionRow>().ok())
+		.find(|row| row.is_visible())
+}
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::SettingsNavGui;
+use crate::{a11y, i18n::tr};
+use gtk4::{
+	glib::{self, clone},
+	prelude::*,
+	Align, Button, Label, Orientation,
+};
+use std::{
+	cell::{Cell, RefCell},
+	fs,
+	path::PathBuf,
+	rc::Rc,
+};
+
+/// Records the pages that have been shown, so that the user can go back and forward between them.
+///
+/// The last page shown is saved to `$XDG_DATA_HOME/cosmic-settings/last-page`, so that it can be
+/// shown again on the next launch.
+#[derive(Clone)]
+pub struct Navigation {
+	/// The button that goes back a page.
+	pub back_button: Button,
+	/// The button that goes forward a page.
+	pub forward_button: Button,
+	/// The box showing where the current page is, such as `Desktop › Dock`.
+	pub breadcrumb: gtk4::Box,
+	nav: SettingsNavGui,
+	/// Pages shown before the current one, with the most recent last.
+	back: Rc<RefCell<Vec<String>>>,
+	/// Pages that were gone back from, with the most recent last.
+	forward: Rc<RefCell<Vec<String>>>,
+	/// The path of the page that is currently shown.
+	current: Rc<RefCell<Option<String>>>,
+	/// Whether a page is being shown by going back or forward, which shouldn't be recorded.
+	moving: Rc<Cell<bool>>,
+	/// The page that was shown when the app was last closed.
+	last_page: Rc<RefCell<Option<String>>>,
+}
+
+impl Navigation {
+	pub fn new(header_box: &gtk4::Box, nav: &SettingsNavGui) -> Self {
+		let back_button = Self::create_button(header_box, "go-previous-symbolic", &tr("Back"));
+		let forward_button = Self::create_button(header_box, "go-next-symbolic", &tr("Forward"));
+		let breadcrumb = Self::create_breadcrumb(header_box);
+		let last_page = fs::read_to_string(last_page_path())
+			.ok()
+			.map(|path| path.trim().to_string())
+			.filter(|path| !path.is_empty());
+		let navigation = Self {
+			back_button,
+			forward_button,
+			breadcrumb,
+			nav: nav.clone(),
+			back: Rc::default(),
+			forward: Rc::default(),
+			current: Rc::default(),
+			moving: Rc::default(),
+			last_page: Rc::new(RefCell::new(last_page)),
+		};
+		navigation
+			.back_button
+			.connect_clicked(clone!(@strong navigation => move |_| {
+				navigation.back();
+			}));
+		navigation
+			.forward_button
+			.connect_clicked(clone!(@strong navigation => move |_| {
+				navigation.forward();
+			}));
+		navigation.update();
+		navigation
+	}
+
+	/// Records that the page at the given path is now shown.
+	pub fn visit(&self, path: &str) {
+		{
+			let mut current = self.current.borrow_mut();
+			if current.as_deref() == Some(path) {
+				return;
+			}
+			if !self.moving.get() {
+				if let Some(previous) = current.take() {
+					self.back.borrow_mut().push(previous);
+				}
+				self.forward.borrow_mut().clear();
+			}
+			*current = Some(path.to_string());
+		}
+		let file = last_page_path();
+		let saved = file
+			.parent()
+			.map_or(Ok(()), fs::create_dir_all)
+			.and_then(|_| fs::write(&file, path));
+		if let Err(err) = saved {
+			warn!(%err, "Failed to save the last page");
+		}
+		self.update();
+	}
+
+	/// Shows the page that was shown when the app was last closed, forgetting any pages that
+	/// were shown while the window was being built.
+	pub fn restore(&self) {
+		if let Some(path) = self.last_page.borrow_mut().take() {
+			if !self.nav.show_page(&path) {
+				warn!(%path, "The last page shown no longer exists");
+			}
+		}
+		self.back.borrow_mut().clear();
+		self.forward.borrow_mut().clear();
+		self.update();
+	}
+
+	/// Shows the page before the current one, returning `false` if there is none.
+	pub fn back(&self) -> bool {
+		self.step(&self.back, &self.forward)
+	}
+
+	/// Shows the page that was last gone back from, returning `false` if there is none.
+	pub fn forward(&self) -> bool {
+		self.step(&self.forward, &self.back)
+	}
+
+	fn step(&self, from: &RefCell<Vec<String>>, to: &RefCell<Vec<String>>) -> bool {
+		let path = match from.borrow_mut().pop() {
+			Some(path) => path,
+			None => return false,
+		};
+		let previous = self.current.borrow().clone();
+		self.moving.set(true);
+		let shown = self.nav.show_page(&path);
+		self.moving.set(false);
+		if !shown {
+			// The page no longer exists, so skip over it.
+			return self.step(from, to);
+		}
+		to.borrow_mut().extend(previous);
+		self.update();
+		true
+	}
+
+	/// Updates the buttons and breadcrumb to match the current page.
+	fn update(&self) {
+		self.back_button
+			.set_sensitive(!self.back.borrow().is_empty());
+		self.forward_button
+			.set_sensitive(!self.forward.borrow().is_empty());
+
+		while let Some(child) = self.breadcrumb.first_child() {
+			self.breadcrumb.remove(&child);
+		}
+		let current = match self.current.borrow().clone() {
+			Some(current) => current,
+			None => return,
+		};
+		let title = match self
+			.nav
+			.pages
+			.borrow()
+			.iter()
+			.find(|page| page.path == current)
+		{
+			Some(page) => page.title.clone(),
+			None => return,
+		};
+		let segments = title.split(" › ").collect::<Vec<_>>();
+		let paths = current.split('/').collect::<Vec<_>>();
+		for (index, segment) in segments.iter().enumerate() {
+			if index > 0 {
+				self.breadcrumb.append(&Label::new(Some("›")));
+			}
+			// Every segment but the last leads to the first page of its section.
+			if index + 1 < segments.len() && index < paths.len() {
+				let path = paths[..=index].join("/");
+				let button = Button::builder()
+					.label(segment)
+					.css_classes(vec!["flat".into(), "breadcrumb".into()])
+					.build();
+				button.connect_clicked(clone!(@strong self.nav as nav => move |_| {
+					nav.show_page(&path);
+				}));
+				self.breadcrumb.append(&button);
+			} else {
+				let label = Label::builder()
+					.label(segment)
+					.css_classes(vec!["breadcrumb-current".into()])
+					.build();
+				self.breadcrumb.append(&label);
+			}
+		}
+	}
+
+	fn create_button(header_box: &gtk4::Box, icon: &str, tooltip: &str) -> Button {
+		let button = Button::builder()
+			.icon_name(icon)
+			.tooltip_text(tooltip)
+			.margin_top(10)
+			.css_classes(vec!["nav-history-button".into()])
+			.sensitive(false)
+			.build();
+		a11y::set_label(&button, tooltip);
+		header_box.append(&button);
+		button
+	}
+
+	fn create_breadcrumb(header_box: &gtk4::Box) -> gtk4::Box {
+		let breadcrumb = gtk4::Box::builder()
+			.orientation(Orientation::Horizontal)
+			.valign(Align::Center)
+			.spacing(4)
+			.margin_top(10)
+			.margin_start(10)
+			.margin_end(10)
+			.build();
+		header_box.append(&breadcrumb);
+		breadcrumb
+	}
+}
+
+fn last_page_path() -> PathBuf {
+	glib::user_data_dir()
+		.join("cosmic-settings")
+		.join("last-page")
+}
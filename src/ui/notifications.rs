@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use crate::{a11y, i18n::tr};
+use gtk4::{
+	glib::{self, clone},
+	prelude::*,
+	Align, Button, Label, Orientation, Overlay, Revealer, RevealerTransitionType, Stack, Widget,
+};
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+/// How long a toast is shown before it hides itself.
+const TOAST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Older toasts are dismissed when more than this many are shown at once.
+const MAX_TOASTS: usize = 3;
+
+/// A button shown on a toast or banner.
+#[derive(Clone)]
+pub struct Action {
+	label: String,
+	callback: Rc<dyn Fn()>,
+}
+
+impl Action {
+	pub fn new<F: Fn() + 'static>(label: &str, callback: F) -> Self {
+		Self {
+			label: label.into(),
+			callback: Rc::new(callback),
+		}
+	}
+}
+
+/// A banner that was posted on a page, which stays until it's dismissed.
+#[derive(Clone)]
+pub struct Banner {
+	revealer: Revealer,
+}
+
+impl Banner {
+	/// Hides the banner, such as once the problem it describes is gone.
+	pub fn dismiss(&self) {
+		self.revealer.set_reveal_child(false);
+	}
+}
+
+/// Toasts and banners, for telling the user about errors and confirming what they did.
+///
+/// Toasts briefly float above the whole window, while banners are shown above a page until
+/// they're dismissed.
+#[derive(Clone)]
+pub struct NotificationsGui {
+	/// The overlay containing the toasts, which wraps the rest of the window below popups
+	pub overlay: Overlay,
+	/// The box that toasts are stacked in
+	pub toasts: gtk4::Box,
+	/// The box shown above the content, that contains the banners of the visible page
+	pub banners: gtk4::Box,
+	/// The stack the banners' pages are in
+	content: Stack,
+	/// Every banner, along with the content child it belongs to
+	banner_pages: Rc<RefCell<Vec<(Option<Widget>, Revealer)>>>,
+}
+
+impl NotificationsGui {
+	pub fn new(child: &impl IsA<Widget>, content: &Stack) -> Self {
+		let overlay = Overlay::builder().child(child).build();
+		let toasts = gtk4::Box::builder()
+			.orientation(Orientation::Vertical)
+			.spacing(8)
+			.halign(Align::Center)
+			.valign(Align::End)
+			.margin_bottom(24)
+			.build();
+		overlay.add_overlay(&toasts);
+		let banners = gtk4::Box::builder()
+			.orientation(Orientation::Vertical)
+			.build();
+		let notifications = Self {
+			overlay,
+			toasts,
+			banners,
+			content: content.clone(),
+			banner_pages: Rc::default(),
+		};
+		content.connect_visible_child_name_notify(clone!(@strong notifications => move |_| {
+			notifications.update_banners();
+		}));
+		notifications
+	}
+
+	/// Briefly shows a message at the bottom of the window, with an optional button.
+	pub fn toast(&self, text: &str, action: Option<Action>) {
+		let revealer = Self::create_revealer(RevealerTransitionType::SlideUp);
+		let toast = Self::create_notification("settings-toast", text, action, &revealer);
+		revealer.set_child(Some(&toast));
+		revealer.connect_child_revealed_notify(
+			clone!(@weak self.toasts as toasts => move |revealer| {
+				if !revealer.reveals_child() && !revealer.is_child_revealed() {
+					toasts.remove(revealer);
+				}
+			}),
+		);
+		self.toasts.append(&revealer);
+		revealer.set_reveal_child(true);
+		glib::timeout_add_local_once(
+			TOAST_TIMEOUT,
+			clone!(@weak revealer => move || {
+				revealer.set_reveal_child(false);
+			}),
+		);
+		let shown = std::iter::successors(self.toasts.first_child(), |child| child.next_sibling())
+			.filter_map(|child| child.downcast::<Revealer>().ok())
+			.filter(|toast| toast.reveals_child())
+			.collect::<Vec<_>>();
+		for toast in shown.iter().take(shown.len().saturating_sub(MAX_TOASTS)) {
+			toast.set_reveal_child(false);
+		}
+	}
+
+	/// Shows a message above the page containing `widget` until it's dismissed, with an
+	/// optional button.
+	///
+	/// If the same message is already shown on that page, its banner is returned instead.
+	pub fn banner(&self, widget: &impl IsA<Widget>, text: &str, action: Option<Action>) -> Banner {
+		let page = self.page_of(widget.as_ref());
+		let existing = self
+			.banner_pages
+			.borrow()
+			.iter()
+			.filter(|(banner_page, revealer)| *banner_page == page && revealer.reveals_child())
+			.map(|(_, revealer)| revealer)
+			.find(|revealer| {
+				revealer
+					.child()
+					.and_then(|child| child.first_child())
+					.and_then(|label| label.downcast::<Label>().ok())
+					.map_or(false, |label| label.text() == text)
+			})
+			.cloned();
+		if let Some(revealer) = existing {
+			return Banner { revealer };
+		}
+		let revealer = Self::create_revealer(RevealerTransitionType::SlideDown);
+		let banner = Self::create_notification("settings-banner", text, action, &revealer);
+		revealer.set_child(Some(&banner));
+		revealer.connect_child_revealed_notify(clone!(@weak self.banners as banners, @strong self.banner_pages as banner_pages => move |revealer| {
+			if !revealer.reveals_child() && !revealer.is_child_revealed() {
+				banners.remove(revealer);
+				banner_pages.borrow_mut().retain(|(_, banner)| banner != revealer);
+			}
+		}));
+		self.banners.append(&revealer);
+		self.banner_pages
+			.borrow_mut()
+			.push((page, revealer.clone()));
+		self.update_banners();
+		revealer.set_reveal_child(true);
+		Banner { revealer }
+	}
+
+	/// Only shows the banners that belong to the visible page.
+	fn update_banners(&self) {
+		let visible = self.content.visible_child();
+		for (page, revealer) in self.banner_pages.borrow().iter() {
+			revealer.set_visible(page.is_some() && *page == visible);
+		}
+	}
+
+	/// Returns the child of the content stack that contains a widget.
+	fn page_of(&self, widget: &Widget) -> Option<Widget> {
+		std::iter::successors(Some(widget.clone()), |widget| widget.parent())
+			.find(|widget| widget.parent().as_ref() == Some(self.content.upcast_ref()))
+	}
+
+	fn create_revealer(transition: RevealerTransitionType) -> Revealer {
+		Revealer::builder().transition_type(transition).build()
+	}
+
+	fn create_notification(
+		class: &str,
+		text: &str,
+		action: Option<Action>,
+		revealer: &Revealer,
+	) -> gtk4::Box {
+		let notification = gtk4::Box::builder()
+			.orientation(Orientation::Horizontal)
+			.spacing(16)
+			.css_classes(vec![class.into()])
+			.build();
+		// Screen readers read out notifications as they appear.
+		let label = a11y::status_label();
+		label.set_text(text);
+		label.set_hexpand(true);
+		notification.append(&label);
+		if let Some(action) = action {
+			let button = Button::builder()
+				.label(&action.label)
+				.valign(Align::Center)
+				.css_classes(vec!["settings-button".into()])
+				.build();
+			button.connect_clicked(clone!(@weak revealer => move |_| {
+				revealer.set_reveal_child(false);
+				(action.callback)();
+			}));
+			notification.append(&button);
+		}
+		let close = Button::builder()
+			.icon_name("window-close-symbolic")
+			.valign(Align::Center)
+			.tooltip_text(&tr("Dismiss"))
+			.css_classes(vec!["settings-button".into()])
+			.build();
+		a11y::set_label(&close, &tr("Dismiss"));
+		close.connect_clicked(clone!(@weak revealer => move |_| {
+			revealer.set_reveal_child(false);
+		}));
+		notification.append(&close);
+		notification
+	}
+}
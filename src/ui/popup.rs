@@ -1,10 +1,54 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use crate::{a11y, i18n::tr};
 use gtk4::{
+	gdk::keys::constants as keys,
 	glib::{self, clone},
 	prelude::*,
-	Align, Button, Label, Orientation, Overlay, Revealer, RevealerTransitionType, Stack, Widget,
+	Align, Button, DirectionType, EventControllerKey, Inhibit, Label, Orientation, Overlay,
+	Revealer, RevealerTransitionType, Stack, Widget, Window,
 };
+use std::{cell::RefCell, rc::Rc};
+use tokio::sync::oneshot;
+
+/// A popup that can be shown with [`PopupGui::run`], which gives a result once it's closed.
+pub struct PopupDialog<T> {
+	title: String,
+	child: Widget,
+	buttons: Vec<(String, Rc<dyn Fn() -> T>)>,
+	default: Option<usize>,
+}
+
+impl<T: 'static> PopupDialog<T> {
+	pub fn new(title: &str, child: &impl IsA<Widget>) -> Self {
+		Self {
+			title: title.into(),
+			child: child.clone().upcast(),
+			buttons: Vec::new(),
+			default: None,
+		}
+	}
+
+	/// Adds a button that closes the popup, with the value returned by `response` as its
+	/// result.
+	pub fn button<F: Fn() -> T + 'static>(mut self, label: &str, response: F) -> Self {
+		self.buttons.push((label.into(), Rc::new(response)));
+		self
+	}
+
+	/// Adds a button like [`PopupDialog::button`], which is also pressed by Enter.
+	pub fn default_button<F: Fn() -> T + 'static>(mut self, label: &str, response: F) -> Self {
+		self.default = Some(self.buttons.len());
+		self.button(label, response)
+	}
+}
+
+/// A popup shown by [`PopupGui::run`], which is above the popups shown before it.
+struct PopupLayer {
+	widget: gtk4::Box,
+	/// Closes the popup without a result.
+	dismiss: Rc<dyn Fn()>,
+}
 
 #[derive(Clone)]
 pub struct PopupGui {
@@ -12,10 +56,12 @@ pub struct PopupGui {
 	pub revealer: Revealer,
 	pub stack: Stack,
 	pub label: Label,
+	/// The popups that are waiting for a result, with the top one last
+	layers: Rc<RefCell<Vec<PopupLayer>>>,
 }
 
 impl PopupGui {
-	pub fn new(child: &gtk4::Box) -> Self {
+	pub fn new(child: &impl IsA<Widget>) -> Self {
 		let stack = Self::create_stack();
 		let revealer = Self::create_revealer();
 		let button = Self::create_button(&revealer);
@@ -40,6 +86,7 @@ impl PopupGui {
 			revealer,
 			overlay,
 			label,
+			layers: Rc::default(),
 		}
 	}
 
@@ -61,13 +108,17 @@ impl PopupGui {
 			.build()
 	}
 
-	fn create_button(revealer: &Revealer) -> Button {
-		let button = Button::builder()
-			.label("Close")
+	fn create_close_button() -> Button {
+		Button::builder()
+			.label(&tr("Close"))
 			.halign(Align::End)
 			.valign(Align::Center)
 			.css_classes(vec!["settings-popup-close".into()])
-			.build();
+			.build()
+	}
+
+	fn create_button(revealer: &Revealer) -> Button {
+		let button = Self::create_close_button();
 		button.connect_clicked(clone!(@weak revealer => move |_| {
 			revealer.set_reveal_child(false);
 		}));
@@ -88,4 +139,160 @@ impl PopupGui {
 		let new_overlay = create_overlay();
 		self.stack.add_named(&new_overlay, Some(name));
 	}
+
+	/// Shows a popup above everything else and waits until it's closed, returning the result
+	/// of the button that closed it, or `None` if it was dismissed.
+	///
+	/// Only the top popup can be used while it's shown, and Escape dismisses it.
+	pub async fn run<T: 'static>(&self, dialog: PopupDialog<T>) -> Option<T> {
+		let (tx, rx) = oneshot::channel();
+		let tx = Rc::new(RefCell::new(Some(tx)));
+		let respond = Rc::new(move |result: Option<T>| {
+			if let Some(tx) = tx.borrow_mut().take() {
+				let _ = tx.send(result);
+			}
+		});
+
+		let title = Label::builder()
+			.label(&dialog.title)
+			.halign(Align::Start)
+			.hexpand(true)
+			.css_classes(vec!["settings-popup-title".into()])
+			.build();
+		let close = Self::create_close_button();
+		let top_box = gtk4::Box::new(Orientation::Horizontal, 60);
+		top_box.append(&title);
+		top_box.append(&close);
+		let button_box = gtk4::Box::builder()
+			.orientation(Orientation::Horizontal)
+			.spacing(8)
+			.halign(Align::End)
+			.build();
+		let mut default_button = None;
+		for (index, (label, response)) in dialog.buttons.into_iter().enumerate() {
+			let button = Button::builder()
+				.label(&label)
+				.css_classes(vec!["settings-button".into()])
+				.build();
+			button.connect_clicked(clone!(@strong respond => move |_| {
+				respond(Some(response()));
+			}));
+			if dialog.default == Some(index) {
+				button.add_css_class("suggested-action");
+				default_button = Some(button.clone());
+			}
+			button_box.append(&button);
+		}
+		let internal_box = a11y::dialog(&dialog.title);
+		internal_box.set_spacing(24);
+		internal_box.set_hexpand(true);
+		internal_box.set_halign(Align::Center);
+		internal_box.set_valign(Align::Center);
+		internal_box.add_css_class("settings-popup");
+		internal_box.append(&top_box);
+		internal_box.append(&dialog.child);
+		if button_box.first_child().is_some() {
+			internal_box.append(&button_box);
+		}
+		// Covers the rest of the window, so that it can't be clicked while the popup is shown.
+		let layer = gtk4::Box::builder()
+			.css_classes(vec!["settings-popup-layer".into()])
+			.build();
+		layer.append(&internal_box);
+
+		let dismiss: Rc<dyn Fn()> = Rc::new(clone!(@strong respond => move || respond(None)));
+		close.connect_clicked(clone!(@strong dismiss => move |_| dismiss()));
+		if let Some(default_button) = default_button.clone() {
+			let controller = EventControllerKey::new();
+			controller.connect_key_pressed(move |_, key, _, _| match key {
+				keys::Return | keys::KP_Enter => {
+					default_button.emit_clicked();
+					Inhibit(true)
+				}
+				_ => Inhibit(false),
+			});
+			layer.add_controller(&controller);
+		}
+
+		self.push_layer(PopupLayer {
+			widget: layer.clone(),
+			dismiss,
+		});
+		let window = self
+			.overlay
+			.root()
+			.and_then(|root| root.downcast::<Window>().ok());
+		let previous_default = window.as_ref().and_then(|window| window.default_widget());
+		if let Some(window) = &window {
+			// Entries that activate the default widget press the default button.
+			window.set_default_widget(default_button.as_ref());
+		}
+		dialog.child.child_focus(DirectionType::TabForward);
+
+		let result = rx.await.ok().flatten();
+
+		if let Some(window) = &window {
+			window.set_default_widget(previous_default.as_ref());
+		}
+		self.pop_layer(&layer);
+		result
+	}
+
+	/// Returns whether a popup shown by [`PopupGui::run`] is waiting for a result.
+	pub fn is_running(&self) -> bool {
+		!self.layers.borrow().is_empty()
+	}
+
+	/// Dismisses the top popup shown by [`PopupGui::run`], if there is one.
+	///
+	/// Returns `false` if there was no popup to dismiss.
+	pub fn dismiss(&self) -> bool {
+		let dismiss = match self.layers.borrow().last() {
+			Some(layer) => layer.dismiss.clone(),
+			None => return false,
+		};
+		dismiss();
+		true
+	}
+
+	/// Shows a new popup, which makes everything below it insensitive.
+	fn push_layer(&self, layer: PopupLayer) {
+		self.set_below_sensitive(false);
+		self.overlay.add_overlay(&layer.widget);
+		self.layers.borrow_mut().push(layer);
+	}
+
+	/// Removes a popup, which makes the one below it usable again.
+	fn pop_layer(&self, widget: &gtk4::Box) {
+		self.overlay.remove_overlay(widget);
+		self.layers
+			.borrow_mut()
+			.retain(|layer| layer.widget != *widget);
+		self.set_below_sensitive(true);
+	}
+
+	/// Makes everything below the top popup sensitive or insensitive, which keeps the focus
+	/// inside of the top popup.
+	fn set_below_sensitive(&self, sensitive: bool) {
+		let layers = self.layers.borrow();
+		if let Some(top) = layers.last() {
+			top.widget.set_sensitive(sensitive);
+			if sensitive {
+				top.widget.child_focus(DirectionType::TabForward);
+			}
+			return;
+		}
+		if let Some(child) = self.overlay.child() {
+			child.set_sensitive(sensitive);
+		}
+		self.revealer.set_sensitive(sensitive);
+		let titlebar = self
+			.overlay
+			.root()
+			.and_then(|root| root.downcast::<Window>().ok())
+			.and_then(|window| window.titlebar());
+		if let Some(titlebar) = titlebar {
+			titlebar.set_sensitive(sensitive);
+		}
+	}
 }
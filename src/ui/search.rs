@@ -1,17 +1,25 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use super::SettingsGui;
+use super::{section, SettingsGui};
 use crate::{
-	sections::SettingsGroupStore,
-	widgets::{ListBoxKeywordedRow, SearchBar},
+	a11y,
+	i18n::{tr, tr_n},
+	sections::{PlacedGroup, SettingsGroupStore},
+	widgets::{ListBoxKeywordedRow, SearchBar, SearchItem},
 };
 use fuzzy_matcher::skim::SkimMatcherV2;
 use gtk4::{
-	glib::{self, clone},
+	glib::{self, clone, Continue},
 	prelude::*,
-	ListBox, ScrolledWindow,
+	Label, ListBox, ListBoxRow, Orientation, ScrolledWindow, Widget,
+};
+use libcosmic_widgets::LabeledItem;
+use std::{
+	cell::{Cell, RefCell},
+	collections::HashSet,
+	rc::Rc,
+	time::Duration,
 };
-use std::{cell::RefCell, rc::Rc};
 
 /// A struct containing references to search-related elements
 #[derive(Clone)]
@@ -20,7 +28,9 @@ pub struct SearchGui {
 	pub bar: SearchBar,
 	/// The ListBox containing all the search results
 	pub all_results: ListBox,
-	/// The scrollable window containing `all_results`
+	/// The label above the results saying how many there are
+	pub status: Label,
+	/// The scrollable window containing `status` and `all_results`
 	pub all_results_scroll: ScrolledWindow,
 	/// The name of the content child that was present *before* searching
 	pub before_search_child: RefCell<Option<String>>,
@@ -32,42 +42,264 @@ impl SearchGui {
 	}
 
 	pub fn setup(&self, ui: Rc<SettingsGui>, sections_store: SettingsGroupStore) {
-		let all_settings_groups = sections_store.borrow();
-		for group in all_settings_groups.iter() {
-			let keyworded_row = ListBoxKeywordedRow::new();
-			keyworded_row.set_group(ui.clone(), &**group);
-			self.all_results.append(&keyworded_row);
-		}
-		let matcher = Rc::new(SkimMatcherV2::default());
-		self.all_results.set_filter_func(
-			clone!(@strong self.bar as bar, @strong matcher => move |row| {
-				let text = bar.text();
+		self.all_results.connect_row_activated(
+			clone!(@weak ui, @strong sections_store => move |_, row| {
 				let row = row
 					.downcast_ref::<ListBoxKeywordedRow>()
 					.expect("invalid object");
-				row.matches(matcher.clone(), text.as_str())
+				Self::open_result(&ui, &sections_store, row);
+			}),
+		);
+		self.all_results.set_filter_func(|row| {
+			let row = row
+				.downcast_ref::<ListBoxKeywordedRow>()
+				.expect("invalid object");
+			row.available() && row.score().is_some()
+		});
+		// Best matches first
+		self.all_results.set_sort_func(|a, b| {
+			let a = a
+				.downcast_ref::<ListBoxKeywordedRow>()
+				.expect("invalid object");
+			let b = b
+				.downcast_ref::<ListBoxKeywordedRow>()
+				.expect("invalid object");
+			b.score().cmp(&a.score()).into()
+		});
+		let matcher = Rc::new(SkimMatcherV2::default());
+		let indexed = Cell::new(false);
+		self.bar
+			.connect_changed(clone!(@weak ui, @strong matcher => move |_| {
+				// The index is built from the rows that groups describe, so pages don't need
+				// to be built to be searched.
+				if !indexed.replace(true) {
+					ui.search.index(&sections_store);
+				}
+				ui.search.update_results(&matcher);
+				let text = ui.search.bar.text();
+				if !text.as_str().trim().is_empty() {
+					if ui.search.before_search_child.borrow().is_none() {
+						*ui.search.before_search_child.borrow_mut() = ui.content.visible_child_name().map(|name| name.as_str().to_string());
+					}
+					ui.content.set_visible_child_name("_search");
+				} else if let Some(name) = ui.search.before_search_child.borrow_mut().take() {
+					ui.content.set_visible_child_name(name.as_str());
+				}
+			}));
+	}
+
+	/// Selects the result `offset` rows after the selected one, skipping any that are hidden.
+	/// Nothing happens if there is no such result.
+	pub fn select_result(&self, offset: i32) {
+		let results = self.visible_results();
+		let selected = self
+			.all_results
+			.selected_row()
+			.and_then(|selected| results.iter().position(|row| *row == selected));
+		let index = match selected {
+			Some(index) => index as i32 + offset,
+			None if offset > 0 => offset - 1,
+			None => return,
+		};
+		if let Some(row) = usize::try_from(index)
+			.ok()
+			.and_then(|index| results.get(index))
+		{
+			self.all_results.select_row(Some(row));
+		}
+	}
+
+	/// Opens the selected result, or the best one if none is selected.
+	///
+	/// Returns `false` if there are no results.
+	pub fn activate_result(&self) -> bool {
+		let row = self
+			.all_results
+			.selected_row()
+			.filter(|row| row.is_child_visible())
+			.or_else(|| self.visible_results().into_iter().next());
+		match row {
+			Some(row) => {
+				row.emit_activate();
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Returns the results that match the current search, in the order they're shown.
+	fn visible_results(&self) -> Vec<ListBoxRow> {
+		(0..)
+			.map_while(|index| self.all_results.row_at_index(index))
+			.filter(|row| row.is_child_visible())
+			.collect()
+	}
+
+	/// Adds a result for every group and setting row.
+	fn index(&self, sections_store: &SettingsGroupStore) {
+		for (index, placed) in sections_store.borrow().iter().enumerate() {
+			for item in Self::index_group(index, placed) {
+				self.all_results.append(&ListBoxKeywordedRow::new(item));
+			}
+		}
+	}
+
+	/// Returns a search item for every setting row that a group describes, as well as one for
+	/// the group itself, which is shown when none of its rows match.
+	fn index_group(index: usize, placed: &PlacedGroup) -> Vec<SearchItem> {
+		let group_title = placed.group.title();
+		let keywords = placed.group.keywords();
+		let mut items = Vec::new();
+		if !group_title.is_empty() || !keywords.is_empty() {
+			items.push(SearchItem {
+				title: if group_title.is_empty() {
+					placed.page.title.clone()
+				} else {
+					group_title.clone()
+				},
+				description: String::new(),
+				keywords,
+				location: placed.page.title.clone(),
+				page_path: placed.page.path.clone(),
+				group: index,
+				whole_group: true,
+				available: placed.available.clone(),
+				card: placed.card.clone(),
+			});
+		}
+		let location = if group_title.is_empty() {
+			placed.page.title.clone()
+		} else {
+			format!("{} › {}", placed.page.title, group_title)
+		};
+		for row in placed.group.rows() {
+			items.push(SearchItem {
+				title: row.title,
+				description: row.description.unwrap_or_default(),
+				keywords: Vec::new(),
+				location: location.clone(),
+				page_path: placed.page.path.clone(),
+				group: index,
+				whole_group: false,
+				available: placed.available.clone(),
+				card: placed.card.clone(),
+			});
+		}
+		items
+	}
+
+	/// Scores every result against the text in the search bar, then filters and sorts them.
+	fn update_results(&self, matcher: &SkimMatcherV2) {
+		let query = self.bar.text().trim().to_lowercase();
+		let rows = (0..)
+			.map_while(|index| self.all_results.row_at_index(index))
+			.map(|row| {
+				row.downcast::<ListBoxKeywordedRow>()
+					.expect("invalid object")
+			})
+			.collect::<Vec<_>>();
+		// Groups only show up as a whole when none of their individual rows match.
+		let mut matched_groups = HashSet::new();
+		for row in rows.iter().filter(|row| !row.whole_group()) {
+			if row.update_score(matcher, &query).is_some() {
+				matched_groups.insert(row.group());
+			}
+		}
+		for row in rows.iter().filter(|row| row.whole_group()) {
+			row.update_score(matcher, &query);
+			if matched_groups.contains(&row.group()) {
+				row.clear_score();
+			}
+		}
+		self.all_results.unselect_all();
+		self.all_results.invalidate_filter();
+		self.all_results.invalidate_sort();
+
+		let count = self.visible_results().len();
+		let status = match count {
+			0 => tr("No results"),
+			count => tr_n("{count} result", "{count} results", count)
+				.replace("{count}", &count.to_string()),
+		};
+		self.status.set_text(&status);
+	}
+
+	/// Switches to the page of a search result, then scrolls to and highlights its setting.
+	fn open_result(
+		ui: &Rc<SettingsGui>,
+		sections_store: &SettingsGroupStore,
+		row: &ListBoxKeywordedRow,
+	) {
+		// Forget the page shown before searching, so clearing the search doesn't return to it.
+		ui.search.before_search_child.replace(None);
+		ui.search.bar.clear();
+		let path = row.page_path();
+		if !ui.nav.show_page(&path) {
+			warn!(%path, "Search result is on an unknown page");
+			return;
+		}
+		// The page is otherwise built once it's shown, which is too late to find the row in it.
+		section::build_page(ui, sections_store, &path);
+		let card = row.card();
+		let target = if row.whole_group() {
+			card.upcast()
+		} else {
+			find_row(card.upcast_ref(), &row.title()).unwrap_or_else(|| card.upcast())
+		};
+		Self::scroll_to(&target);
+		target.add_css_class("search-highlight");
+		glib::timeout_add_local_once(
+			Duration::from_millis(1500),
+			clone!(@weak target => move || {
+				target.remove_css_class("search-highlight");
 			}),
 		);
-		self.bar.connect_changed(clone!(@weak ui => move |_| {
-			ui.search.all_results.invalidate_filter();
-			let text = ui.search.bar.text();
-			if !text.as_str().trim().is_empty() {
-				if ui.search.before_search_child.borrow().is_none() {
-					*ui.search.before_search_child.borrow_mut() = ui.content.visible_child_name().map(|name| name.as_str().to_string());
+	}
+
+	/// Scrolls the page containing a widget so that the widget is at the top.
+	fn scroll_to(target: &Widget) {
+		// A page that has never been shown has no size yet, so wait until it has been laid out.
+		target.add_tick_callback(|target, _| {
+			if target.height() == 0 {
+				return Continue(true);
+			}
+			let scroll = target
+				.ancestor(ScrolledWindow::static_type())
+				.and_then(|scroll| scroll.downcast::<ScrolledWindow>().ok());
+			if let Some(scroll) = scroll {
+				let panel = scroll.child().and_then(|viewport| viewport.first_child());
+				if let Some((_, y)) =
+					panel.and_then(|panel| target.translate_coordinates(&panel, 0.0, 0.0))
+				{
+					scroll.vadjustment().set_value(y);
 				}
-				ui.content.set_visible_child_name("_search");
-			} else if let Some(name) = ui.search.before_search_child.borrow_mut().take() {
-				ui.content.set_visible_child_name(name.as_str());
 			}
-		}));
+			Continue(false)
+		});
 	}
 
 	fn create_all_results() -> ListBox {
-		ListBox::new()
+		let all_results = ListBox::new();
+		a11y::set_label(&all_results, &tr("Search results"));
+		all_results
 	}
 
-	fn create_all_results_scroll(all_results: &ListBox) -> ScrolledWindow {
-		ScrolledWindow::builder().child(all_results).build()
+	fn create_status() -> Label {
+		let status = a11y::status_label();
+		status.set_margin_top(8);
+		status.set_margin_start(12);
+		status.set_margin_end(12);
+		status.add_css_class("settings-entry-desc");
+		status
+	}
+
+	fn create_all_results_scroll(status: &Label, all_results: &ListBox) -> ScrolledWindow {
+		let results_box = gtk4::Box::builder()
+			.orientation(Orientation::Vertical)
+			.build();
+		results_box.append(status);
+		results_box.append(all_results);
+		ScrolledWindow::builder().child(&results_box).build()
 	}
 
 	fn create_bar() -> SearchBar {
@@ -82,13 +314,39 @@ impl Default for SearchGui {
 	fn default() -> Self {
 		let bar = Self::create_bar();
 		let all_results = Self::create_all_results();
-		let all_results_scroll = Self::create_all_results_scroll(&all_results);
+		let status = Self::create_status();
+		let all_results_scroll = Self::create_all_results_scroll(&status, &all_results);
 		let before_search_child = RefCell::new(None);
 		Self {
 			bar,
 			all_results,
+			status,
 			all_results_scroll,
 			before_search_child,
 		}
 	}
 }
+
+/// Finds the widget of the setting row with the given title inside of a group's card, which
+/// is the [`LabeledItem`] around the label showing that title, or the label's parent if the
+/// row is a custom widget such as a button.
+fn find_row(card: &Widget, title: &str) -> Option<Widget> {
+	let mut pending = vec![card.clone()];
+	while let Some(widget) = pending.pop() {
+		let mut child = widget.last_child();
+		while let Some(current) = child {
+			child = current.prev_sibling();
+			match current.downcast_ref::<Label>() {
+				// Labels directly in the card, such as the group's title, aren't rows.
+				Some(label) if widget != *card && label.text().as_str() == title => {
+					return label
+						.ancestor(LabeledItem::static_type())
+						.filter(|item| item.is_ancestor(card))
+						.or_else(|| label.parent());
+				}
+				_ => pending.push(current),
+			}
+		}
+	}
+	None
+}
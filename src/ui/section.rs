@@ -1,20 +1,54 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use crate::{
-	sections::{Section, SectionLayout, SettingsGroup, SettingsGroupStore},
-	ui::SettingsGui,
+	a11y,
+	config::Source,
+	i18n::tr,
+	sections::{
+		Availability, PlacedGroup, Section, SectionLayout, SettingsGroup, SettingsGroupStore,
+	},
+	ui::{
+		adaptive::Pane,
+		nav::{path_segment, Page},
+		notifications::Action,
+		SettingsGui,
+	},
 	widgets::ListBoxSelectionRow,
 };
 use gtk4::{
 	glib::{self, clone},
 	prelude::*,
-	Align, Image, Label, ListBox, Orientation, PolicyType, ScrolledWindow,
+	Align, Button, Image, Label, ListBox, Orientation, PolicyType, ScrolledWindow,
+};
+use std::{
+	cell::{Cell, RefCell},
+	rc::Rc,
 };
-use std::rc::Rc;
 
 pub fn setup<S: Section>(ui: Rc<SettingsGui>, sections_store: SettingsGroupStore) {
+	add(
+		ui,
+		sections_store,
+		S::NAME,
+		S::ICON,
+		S::layout(),
+		S::availability(),
+	);
+}
+
+/// Adds a section to the nav and its pages to the content, given its name, icon and layout.
+///
+/// The names of the section and its pages are in English, and translated where they're shown.
+pub fn add(
+	ui: Rc<SettingsGui>,
+	sections_store: SettingsGroupStore,
+	name: &'static str,
+	icon: &str,
+	layout: SectionLayout,
+	availability: Availability,
+) {
 	view! {
-		row = ListBoxSelectionRow::new(S::NAME.into()) {
+		row = ListBoxSelectionRow::new(path_segment(name)) {
 			add_css_class: "nav-element",
 			set_margin_top: 8,
 			set_margin_bottom: 8,
@@ -28,19 +62,19 @@ pub fn setup<S: Section>(ui: Rc<SettingsGui>, sections_store: SettingsGroupStore
 				set_margin_top: 10,
 				set_margin_bottom: 10,
 				append: icon = &Image {
-					set_icon_name: Some(S::ICON)
+					set_icon_name: Some(icon)
 				},
 				append: label = &Label {
-					set_text: S::NAME
+					set_text: &tr(name)
 				},
 			}
 		}
 	}
+	a11y::set_label(&row, &tr(name));
 	ui.nav.list.append(&row);
 	ui.nav.labels.borrow_mut().push(label);
 
-	let entries = S::layout();
-	match entries {
+	match layout {
 		SectionLayout::Single(groups) => {
 			// Alright, now we setup the actual settings panel
 			view! {
@@ -54,13 +88,90 @@ pub fn setup<S: Section>(ui: Rc<SettingsGui>, sections_store: SettingsGroupStore
 					}
 				}
 			}
-			setup_single(&panel, ui.clone(), groups, sections_store);
+			let page = Page {
+				path: path_segment(name),
+				title: tr(name),
+				child: path_segment(name),
+			};
+			a11y::set_label(&scroll_window, &page.title);
+			let page_row = PageRow::new(&ui, &row, Some(&page), None);
+			setup_single(&panel, ui.clone(), &page, &page_row, groups, sections_store);
+			// The page is known before it's added, in case adding it makes it the visible one.
+			ui.nav.pages.borrow_mut().push(page.clone());
 			ui.content
-				.add_titled(&scroll_window, Some(S::NAME), S::NAME);
+				.add_titled(&scroll_window, Some(&page.child), &page.title);
+			availability.connect(move |available| {
+				page_row.section.set(available);
+				page_row.update();
+			});
 		}
 		SectionLayout::Multiple(subsections) => {
-			setup_multi(S::NAME, ui, subsections, sections_store);
+			setup_multi(name, ui.clone(), subsections, sections_store);
 			row.set_subsection(true);
+			let section_row = PageRow::new(&ui, &row, None, None);
+			availability.connect(move |available| {
+				section_row.section.set(available);
+				section_row.update();
+			});
+		}
+	}
+}
+
+/// Shows the nav row of a page only while its section is available, and while at least one of
+/// the groups on the page is, unless the page has no groups at all.
+#[derive(Clone)]
+struct PageRow {
+	ui: Rc<SettingsGui>,
+	row: ListBoxSelectionRow,
+	/// The name of the page in the content stack, if the row leads to a single page.
+	child: Option<String>,
+	/// The path of the section to show instead, if this is one of its pages.
+	section_path: Option<String>,
+	/// Whether the section of the page is available.
+	section: Rc<Cell<bool>>,
+	/// Whether each group on the page is available.
+	groups: Rc<RefCell<Vec<Rc<Cell<bool>>>>>,
+}
+
+impl PageRow {
+	fn new(
+		ui: &Rc<SettingsGui>,
+		row: &ListBoxSelectionRow,
+		page: Option<&Page>,
+		section_path: Option<String>,
+	) -> Self {
+		Self {
+			ui: ui.clone(),
+			row: row.clone(),
+			child: page.map(|page| page.child.clone()),
+			section_path,
+			section: Rc::new(Cell::new(true)),
+			groups: Rc::default(),
+		}
+	}
+
+	fn update(&self) {
+		let visible = {
+			let groups = self.groups.borrow();
+			self.section.get() && (groups.is_empty() || groups.iter().any(|group| group.get()))
+		};
+		if self.row.is_visible() == visible {
+			return;
+		}
+		self.row.set_visible(visible);
+		let shown = self.row.is_selected()
+			|| self.child.as_deref().map_or(false, |child| {
+				self.ui.content.visible_child_name().as_deref() == Some(child)
+			});
+		if !visible && shown {
+			// Move away from a page that's no longer available.
+			let moved = match &self.section_path {
+				Some(section) => self.ui.nav.show_page(section),
+				None => false,
+			};
+			if !moved {
+				self.ui.nav.show_first_page();
+			}
 		}
 	}
 }
@@ -68,6 +179,8 @@ pub fn setup<S: Section>(ui: Rc<SettingsGui>, sections_store: SettingsGroupStore
 fn setup_single(
 	panel: &gtk4::Box,
 	ui: Rc<SettingsGui>,
+	page: &Page,
+	page_row: &PageRow,
 	groups: Vec<Box<dyn SettingsGroup>>,
 	sections_store: SettingsGroupStore,
 ) {
@@ -77,22 +190,147 @@ fn setup_single(
 			group_box = gtk4::Box {
 				set_orientation: Orientation::Vertical,
 				set_spacing: 8,
-				append: group_box_inner = &gtk4::Box {
+				append: group_box_inner = &a11y::group(&title) {
 					add_css_class: "settings-group",
 					set_orientation: Orientation::Vertical,
 					set_spacing: 16,
 					append: group_title = &Label {
 						add_css_class: "settings-group-title",
-						set_label: title,
+						set_label: &title,
 						set_halign: Align::Start,
 						set_visible: !title.is_empty()
 					}
 				}
 			}
 		}
-		group.layout(&group_box_inner, ui.clone());
 		panel.append(&group_box);
-		sections_store.borrow_mut().push(group);
+		let available = Rc::new(Cell::new(true));
+		page_row.groups.borrow_mut().push(available.clone());
+		group.availability().connect(
+			clone!(@weak group_box, @weak ui.search.all_results as results, @strong available, @strong page_row => move |is_available| {
+				available.set(is_available);
+				group_box.set_visible(is_available);
+				// Search results are filtered by whether their group is available.
+				results.invalidate_filter();
+				page_row.update();
+			}),
+		);
+		sections_store.borrow_mut().push(PlacedGroup {
+			group,
+			page: page.clone(),
+			card: group_box_inner,
+			available,
+			built: Cell::new(false),
+		});
+	}
+	build_on_visit(&ui, &sections_store, page);
+}
+
+/// Lays out the groups of a page the first time it's shown, so that pages which are never
+/// visited cost nothing.
+fn build_on_visit(ui: &Rc<SettingsGui>, sections_store: &SettingsGroupStore, page: &Page) {
+	let (child, path) = (page.child.clone(), page.path.clone());
+	ui.content.connect_visible_child_name_notify(
+		clone!(@weak ui, @strong sections_store => move |content| {
+			if content.visible_child_name().as_deref() != Some(child.as_str()) {
+				return;
+			}
+			// The first page added is shown right away, even when another page is about to be
+			// restored, so wait to see whether this page is still shown.
+			glib::idle_add_local_once(clone!(@weak ui, @strong sections_store, @strong child, @strong path => move || {
+				if ui.content.visible_child_name().as_deref() == Some(child.as_str()) {
+					build_page(&ui, &sections_store, &path);
+				}
+			}));
+		}),
+	);
+}
+
+/// Lays out the groups of the page at the given path, unless they already have been.
+pub fn build_page(ui: &Rc<SettingsGui>, sections_store: &SettingsGroupStore, path: &str) {
+	for placed in sections_store.borrow().iter() {
+		if placed.page.path == path {
+			build_group(ui, placed);
+		}
+	}
+}
+
+fn build_group(ui: &Rc<SettingsGui>, placed: &PlacedGroup) {
+	if placed.built.replace(true) {
+		return;
+	}
+	placed.group.layout(&placed.card, ui.clone());
+	let keys = placed.group.keys();
+	let mut reasons = Vec::new();
+	for lock in keys.iter().filter_map(|key| ui.config.lock(key)) {
+		let reason = lock.describe();
+		if !reasons.contains(&reason) {
+			reasons.push(reason);
+		}
+	}
+	for reason in reasons {
+		view! {
+			locked = gtk4::Box {
+				set_orientation: Orientation::Horizontal,
+				set_spacing: 8,
+				add_css_class: "settings-group-locked",
+				append: padlock = &Image {
+					set_icon_name: Some("changes-prevent-symbolic")
+				},
+				append: reason_label = &Label {
+					set_text: &reason,
+					set_halign: Align::Start,
+					set_wrap: true
+				}
+			}
+		}
+		placed.card.append(&locked);
+	}
+	// Locked keys keep their locked value, so only the others can be reset.
+	let keys = keys
+		.iter()
+		.copied()
+		.filter(|key| ui.config.lock(key).is_none())
+		.collect::<Vec<_>>();
+	if !keys.is_empty() {
+		let reset_button = Button::builder()
+			.label(&tr("Reset to Defaults"))
+			.halign(Align::End)
+			.css_classes(vec!["settings-button".into()])
+			.build();
+		let title = match placed.group.title() {
+			title if title.is_empty() => placed.page.title.clone(),
+			title => title,
+		};
+		reset_button.connect_clicked(clone!(@strong ui => move |_| {
+			let old = keys.iter().map(|key| (*key, ui.config.get(key))).collect::<Vec<_>>();
+			let mut failed = false;
+			for key in &keys {
+				if let Err(err) = ui.config.reset(key, Source::Reset) {
+					error!(?err, %key, "Failed to reset setting");
+					failed = true;
+				}
+			}
+			if failed {
+				ui.notifications.toast(
+					&tr("Some {title} settings couldn't be reset").replace("{title}", &title),
+					None,
+				);
+				return;
+			}
+			let undo = Action::new(&tr("Undo"), clone!(@strong ui => move || {
+				for (key, value) in &old {
+					if let Err(err) = ui.config.set(key, value.clone(), Source::Revert) {
+						error!(?err, %key, "Failed to restore setting");
+					}
+				}
+			}));
+			ui.notifications.toast(
+				&tr("{title} was reset to defaults").replace("{title}", &title),
+				Some(undo),
+			);
+		}));
+		placed.card.append(&reset_button);
 	}
 }
 
@@ -111,14 +349,16 @@ fn setup_multi(
 			set_margin_end: 10,
 		}
 	}
-	for (name, groups) in sections {
+	a11y::set_label(&nav, &tr(name));
+	for (page, groups) in sections {
+		let path = format!("{}/{}", path_segment(name), path_segment(page));
 		view! {
-			row = ListBoxSelectionRow::new(name.into()) {
+			row = ListBoxSelectionRow::new(path.clone()) {
 				add_css_class: "nav-element",
 				set_margin_top: 8,
 				set_margin_bottom: 8,
 				set_child: label = Some(&Label) {
-					set_text: name,
+					set_text: &tr(page),
 					set_margin_top: 5,
 					set_margin_bottom: 5,
 					set_margin_end: 8,
@@ -126,6 +366,7 @@ fn setup_multi(
 				}
 			}
 		}
+		a11y::set_label(&row, &tr(page));
 		nav.append(&row);
 		// Set up the actual groups
 		view! {
@@ -139,15 +380,36 @@ fn setup_multi(
 				}
 			}
 		}
-		setup_single(&panel, ui.clone(), groups, sections_store.clone());
-		ui.content.add_named(&scroll_window, Some(name));
+		// Pages are named by their path in the content stack, since page names are only unique
+		// within their section.
+		let page = Page {
+			child: path.clone(),
+			path,
+			title: format!("{} › {}", tr(name), tr(page)),
+		};
+		a11y::set_label(&scroll_window, &page.title);
+		let page_row = PageRow::new(&ui, &row, Some(&page), Some(path_segment(name)));
+		setup_single(
+			&panel,
+			ui.clone(),
+			&page,
+			&page_row,
+			groups,
+			sections_store.clone(),
+		);
+		ui.nav.pages.borrow_mut().push(page.clone());
+		ui.content.add_named(&scroll_window, Some(&page.child));
 	}
 	let main_stack = &ui.content;
-	nav.connect_row_activated(clone!(@weak main_stack, => move |_, row| {
-		let row = row
-			.downcast_ref::<ListBoxSelectionRow>()
-			.expect("invalid object");
-		main_stack.set_visible_child_name(&row.row_id());
-	}));
-	ui.nav.stack.add_named(&nav, Some(name));
+	nav.connect_row_activated(
+		clone!(@weak main_stack, @strong ui.adaptive as adaptive => move |_, row| {
+			let row = row
+				.downcast_ref::<ListBoxSelectionRow>()
+				.expect("invalid object");
+			main_stack.set_visible_child_name(&row.row_id());
+			// Choosing the page that's already shown doesn't change the content stack.
+			adaptive.show_pane(Pane::Page);
+		}),
+	);
+	ui.nav.stack.add_named(&nav, Some(&path_segment(name)));
 }
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use super::SettingsGui;
+use gtk4::{
+	gdk::{keys::constants as keys, ModifierType},
+	gio::SimpleAction,
+	glib::clone,
+	prelude::*,
+	Application, ApplicationWindow, EventControllerKey, Inhibit, PropagationPhase,
+};
+use std::rc::Rc;
+
+/// Sets up the keyboard shortcuts of the settings window.
+pub fn setup(application: &Application, window: &ApplicationWindow, ui: Rc<SettingsGui>) {
+	let search = SimpleAction::new("search", None);
+	search.connect_activate(clone!(@weak ui => move |_, _| {
+		// Only the popup can be used while it's shown.
+		if ui.popup.is_running() {
+			return;
+		}
+		ui.search.bar.open();
+	}));
+	window.add_action(&search);
+	application.set_accels_for_action("win.search", &["<Primary>f"]);
+
+	let back = SimpleAction::new("back", None);
+	back.connect_activate(clone!(@weak ui => move |_, _| {
+		if ui.popup.is_running() {
+			return;
+		}
+		ui.search.bar.clear();
+		ui.navigation.back();
+	}));
+	window.add_action(&back);
+	application.set_accels_for_action("win.back", &["<Alt>Left"]);
+
+	let forward = SimpleAction::new("forward", None);
+	forward.connect_activate(clone!(@weak ui => move |_, _| {
+		if ui.popup.is_running() {
+			return;
+		}
+		ui.search.bar.clear();
+		ui.navigation.forward();
+	}));
+	window.add_action(&forward);
+	application.set_accels_for_action("win.forward", &["<Alt>Right"]);
+
+	let close = SimpleAction::new("close", None);
+	close.connect_activate(clone!(@weak window => move |_, _| {
+		window.close();
+	}));
+	window.add_action(&close);
+	application.set_accels_for_action("win.close", &["<Primary>w"]);
+
+	let quit = SimpleAction::new("quit", None);
+	quit.connect_activate(clone!(@weak application => move |_, _| {
+		application.quit();
+	}));
+	application.add_action(&quit);
+	application.set_accels_for_action("app.quit", &["<Primary>q"]);
+
+	setup_escape(window, &ui);
+	setup_results(&ui);
+}
+
+/// Escape dismisses the top popup if one is shown, and otherwise clears the search.
+fn setup_escape(window: &ApplicationWindow, ui: &Rc<SettingsGui>) {
+	let controller = EventControllerKey::new();
+	controller.connect_key_pressed(
+		clone!(@weak ui => @default-return Inhibit(false), move |_, key, _, _| {
+			if key != keys::Escape {
+				return Inhibit(false);
+			}
+			if ui.popup.dismiss() {
+				return Inhibit(true);
+			}
+			if ui.popup.revealer.reveals_child() {
+				ui.popup.revealer.set_reveal_child(false);
+			} else if ui.search.bar.is_open() {
+				ui.search.bar.clear();
+			} else {
+				return Inhibit(false);
+			}
+			Inhibit(true)
+		}),
+	);
+	window.add_controller(&controller);
+}
+
+/// Up and Down move through the search results while typing in the search bar, and
+/// Enter opens the selected one.
+fn setup_results(ui: &Rc<SettingsGui>) {
+	let controller = EventControllerKey::new();
+	// The entry would otherwise handle these keys itself.
+	controller.set_propagation_phase(PropagationPhase::Capture);
+	controller.connect_key_pressed(
+		clone!(@weak ui => @default-return Inhibit(false), move |_, key, _, state| {
+			if state.intersects(ModifierType::CONTROL_MASK | ModifierType::ALT_MASK) {
+				return Inhibit(false);
+			}
+			if ui.search.bar.text().trim().is_empty() {
+				return Inhibit(false);
+			}
+			match key {
+				keys::Down => ui.search.select_result(1),
+				keys::Up => ui.search.select_result(-1),
+				keys::Return | keys::KP_Enter => return Inhibit(ui.search.activate_result()),
+				_ => return Inhibit(false),
+			}
+			Inhibit(true)
+		}),
+	);
+	ui.search.bar.add_controller(&controller);
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use gtk4::{
+	gdk::{Toplevel, ToplevelState},
+	glib::{self, clone},
+	prelude::*,
+	Widget,
+};
+use std::{
+	cell::{Cell, RefCell},
+	rc::Rc,
+};
+use tokio::sync::watch;
+
+/// Returns whether a widget can be seen, which is while it's mapped and its window isn't
+/// minimized. A widget on a page of the content stack is only mapped while that page is shown.
+///
+/// Background work for a settings group, such as scanning for networks, should only be done
+/// while its widgets can be seen. The receiver closes when the widget is destroyed.
+pub fn track(widget: &impl IsA<Widget>) -> watch::Receiver<bool> {
+	let widget = widget.clone().upcast::<Widget>();
+	let last = Rc::new(Cell::new(is_visible(&widget)));
+	let (tx, rx) = watch::channel(last.get());
+	// Taken when the widget is destroyed, which closes the receiver.
+	let tx = Rc::new(RefCell::new(Some(tx)));
+	let update = Rc::new(clone!(@weak widget, @strong tx => move || {
+		let visible = is_visible(&widget);
+		if let Some(tx) = tx.borrow().as_ref() {
+			if last.replace(visible) != visible {
+				let _ = tx.send(visible);
+			}
+		}
+	}));
+	widget.connect_map(clone!(@strong update => move |_| update()));
+	widget.connect_unmap(clone!(@strong update => move |_| update()));
+	widget.connect_realize(clone!(@strong update => move |widget| {
+		if let Some(toplevel) = toplevel(widget) {
+			toplevel.connect_state_notify(clone!(@strong update => move |_| update()));
+		}
+	}));
+	widget.connect_destroy(move |_| {
+		tx.borrow_mut().take();
+	});
+	rx
+}
+
+fn is_visible(widget: &Widget) -> bool {
+	let minimized = toplevel(widget).map_or(false, |toplevel| {
+		toplevel.state().contains(ToplevelState::MINIMIZED)
+	});
+	widget.is_mapped() && !minimized
+}
+
+fn toplevel(widget: &Widget) -> Option<Toplevel> {
+	widget
+		.native()
+		.and_then(|native| native.surface().dynamic_cast::<Toplevel>().ok())
+}
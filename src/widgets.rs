@@ -4,6 +4,6 @@ mod keyworded_row;
 mod search_bar;
 mod selection_row;
 
-pub use keyworded_row::ListBoxKeywordedRow;
+pub use keyworded_row::{ListBoxKeywordedRow, SearchItem};
 pub use search_bar::SearchBar;
 pub use selection_row::ListBoxSelectionRow;
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::{sections::SettingsGroup, ui::SettingsGui};
+use crate::a11y;
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use gtk4::{
 	glib::{self, Object},
@@ -8,7 +8,10 @@ use gtk4::{
 	subclass::prelude::*,
 	Align, Label, Orientation,
 };
-use std::{cell::RefCell, rc::Rc};
+use std::{
+	cell::{Cell, RefCell},
+	rc::Rc,
+};
 
 glib::wrapper! {
 	pub struct ListBoxKeywordedRow(ObjectSubclass<ListBoxKeywordedRowImp>)
@@ -16,47 +19,153 @@ glib::wrapper! {
 		@implements gtk4::Accessible, gtk4::Actionable;
 }
 
+/// A single searchable setting row, or a whole group of settings.
+#[derive(Debug)]
+pub struct SearchItem {
+	/// The title that is searched and shown in the result.
+	pub title: String,
+	/// The description that is searched.
+	pub description: String,
+	/// Extra keywords that are searched, in English and the user's language.
+	pub keywords: Vec<String>,
+	/// Where the item is found, such as `Desktop › Dock › Dock Options`.
+	pub location: String,
+	/// The path of the page the item is on.
+	pub page_path: String,
+	/// The index of the group the item belongs to.
+	pub group: usize,
+	/// Whether this item stands for its whole group, rather than a single row.
+	pub whole_group: bool,
+	/// Whether the group the item belongs to is available. Items of unavailable groups are
+	/// never shown.
+	pub available: Rc<Cell<bool>>,
+	/// The card of the group the item belongs to, which contains the widget to scroll to and
+	/// highlight when the result is opened.
+	pub card: gtk4::Box,
+}
+
 impl ListBoxKeywordedRow {
-	pub fn new() -> Self {
-		Self::default()
-	}
+	pub fn new(item: SearchItem) -> Self {
+		let row: Self = Object::new(&[]).expect("Failed to create `ListBoxKeywordedRow`.");
+		let result_box = gtk4::Box::builder()
+			.orientation(Orientation::Vertical)
+			.spacing(4)
+			.margin_top(8)
+			.margin_bottom(8)
+			.margin_start(12)
+			.margin_end(12)
+			.build();
+		let title_label = Label::builder()
+			.label(&item.title)
+			.css_classes(vec!["settings-entry-title".into()])
+			.halign(Align::Start)
+			.build();
+		result_box.append(&title_label);
+		let location_label = Label::builder()
+			.label(&item.location)
+			.css_classes(vec!["settings-entry-desc".into()])
+			.halign(Align::Start)
+			.build();
+		result_box.append(&location_label);
+		row.set_child(Some(&result_box));
+		a11y::set_label(&row, &item.title);
+		a11y::set_description(&row, &item.location);
 
-	pub fn set_group(&self, ui: Rc<SettingsGui>, group: &dyn SettingsGroup) {
-		let group_object = {
-			let group_box = gtk4::Box::builder()
-				.orientation(Orientation::Vertical)
-				.spacing(8)
-				.build();
-			let group_box_inner = gtk4::Box::builder()
-				.orientation(gtk4::Orientation::Vertical)
-				.spacing(16)
-				.css_classes(vec!["settings-group".into()])
-				.build();
-			let title = group.title();
-			if !title.is_empty() {
-				let group_title = Label::builder()
-					.label(title)
-					.css_classes(vec!["settings-group-title".into()])
-					.halign(Align::Start)
-					.build();
-				group_box.append(&group_title);
-			}
-			group_box.append(&group_box_inner);
-			group.layout(&group_box_inner, ui);
-			group_box
-		};
-		self.set_child(Some(&group_object));
-		*self.inner().keywords.borrow_mut() = group.keywords();
+		let inner = row.inner();
+		*inner.title_label.borrow_mut() = Some(title_label);
+		*inner.item.borrow_mut() = Some(item);
+		row
 	}
 
-	pub fn matches(&self, matcher: Rc<SkimMatcherV2>, haystack: &str) -> bool {
-		let haystack = haystack.trim().to_lowercase();
+	/// Scores this row against a search query, highlighting the matched characters of its title.
+	///
+	/// Returns `None` if the query doesn't match.
+	pub fn update_score(&self, matcher: &SkimMatcherV2, query: &str) -> Option<i64> {
 		let inner = self.inner();
-		inner
-			.keywords
+		let item = inner.item.borrow();
+		let item = item.as_ref()?;
+		let title = matcher.fuzzy_indices(&item.title, query);
+		// Matches in the title count for more than matches anywhere else.
+		let score = [
+			title.as_ref().map(|(score, _)| score * 2),
+			matcher.fuzzy_match(&item.description, query),
+			item.keywords
+				.iter()
+				.filter_map(|keyword| matcher.fuzzy_match(keyword, query))
+				.max(),
+		]
+		.into_iter()
+		.flatten()
+		.max();
+
+		let indices = title.map(|(_, indices)| indices).unwrap_or_default();
+		let markup = item
+			.title
+			.chars()
+			.enumerate()
+			.map(|(index, c)| {
+				let c = glib::markup_escape_text(&c.to_string());
+				if indices.contains(&index) {
+					format!("<b>{}</b>", c)
+				} else {
+					c.to_string()
+				}
+			})
+			.collect::<String>();
+		if let Some(label) = inner.title_label.borrow().as_ref() {
+			label.set_markup(&markup);
+		}
+		inner.score.set(score);
+		score
+	}
+
+	/// Hides this row from the results, regardless of its score.
+	pub fn clear_score(&self) {
+		self.inner().score.set(None);
+	}
+
+	/// The score of this row against the current search, or `None` if it isn't a result.
+	pub fn score(&self) -> Option<i64> {
+		self.inner().score.get()
+	}
+
+	/// Whether the group this row belongs to is available.
+	pub fn available(&self) -> bool {
+		self.with_item(|item| item.available.get())
+	}
+
+	/// The index of the group this row belongs to.
+	pub fn group(&self) -> usize {
+		self.with_item(|item| item.group)
+	}
+
+	/// Whether this row stands for a whole group, rather than a single setting.
+	pub fn whole_group(&self) -> bool {
+		self.with_item(|item| item.whole_group)
+	}
+
+	/// The path of the page this row's setting is on.
+	pub fn page_path(&self) -> String {
+		self.with_item(|item| item.page_path.clone())
+	}
+
+	/// The title of this row's setting or group.
+	pub fn title(&self) -> String {
+		self.with_item(|item| item.title.clone())
+	}
+
+	/// The card of the group this row belongs to.
+	pub fn card(&self) -> gtk4::Box {
+		self.with_item(|item| item.card.clone())
+	}
+
+	fn with_item<T>(&self, f: impl FnOnce(&SearchItem) -> T) -> T {
+		f(self
+			.inner()
+			.item
 			.borrow()
-			.iter()
-			.any(|needle| matcher.fuzzy_match(needle, &haystack).is_some())
+			.as_ref()
+			.expect("search row has no item"))
 	}
 
 	fn inner(&self) -> &ListBoxKeywordedRowImp {
@@ -64,15 +173,11 @@ impl ListBoxKeywordedRow {
 	}
 }
 
-impl Default for ListBoxKeywordedRow {
-	fn default() -> Self {
-		Object::new(&[]).expect("Failed to create `ListBoxKeywordedRow`.")
-	}
-}
-
 #[derive(Debug, Default)]
 pub struct ListBoxKeywordedRowImp {
-	keywords: RefCell<&'static [&'static str]>,
+	item: RefCell<Option<SearchItem>>,
+	title_label: RefCell<Option<Label>>,
+	score: Cell<Option<i64>>,
 }
 
 #[glib::object_subclass]
@@ -87,6 +192,7 @@ impl ObjectSubclass for ListBoxKeywordedRowImp {
 	fn class_init(klass: &mut Self::Class) {
 		// The layout manager determines how child widgets are laid out.
 		klass.set_layout_manager_type::<gtk4::BinLayout>();
+		klass.set_accessible_role(gtk4::AccessibleRole::ListItem);
 	}
 }
 
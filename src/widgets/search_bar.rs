@@ -1,10 +1,11 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+use crate::{a11y, i18n::tr};
 use gtk4::{
 	glib::{self, clone, GString, Object},
 	prelude::*,
 	subclass::prelude::*,
-	Button, Entry,
+	AccessibleRole, Button, Entry,
 };
 use std::cell::RefCell;
 
@@ -27,6 +28,42 @@ impl SearchBar {
 		self.inner().entry.borrow().text()
 	}
 
+	/// Shows the search entry and replaces its text.
+	pub fn set_text(&self, text: &str) {
+		self.open();
+		self.inner().entry.borrow().set_text(text);
+	}
+
+	/// Whether the search entry is shown.
+	pub fn is_open(&self) -> bool {
+		self.inner().entry.borrow().get_visible()
+	}
+
+	/// Shows the search entry and focuses it.
+	pub fn open(&self) {
+		let inner = self.inner();
+		let entry = inner.entry.borrow();
+		inner.holder.borrow().set_css_classes(&["search-bar"]);
+		entry.show();
+		entry.grab_focus();
+	}
+
+	/// Empties and hides the search entry.
+	pub fn clear(&self) {
+		self.inner().entry.borrow().set_text("");
+		self.close();
+	}
+
+	/// Hides the search entry.
+	pub fn close(&self) {
+		let inner = self.inner();
+		inner
+			.holder
+			.borrow()
+			.set_css_classes(&["search-bar-inactive"]);
+		inner.entry.borrow().hide();
+	}
+
 	fn inner(&self) -> &SearchBarImp {
 		SearchBarImp::from_instance(self)
 	}
@@ -50,6 +87,7 @@ impl ObjectSubclass for SearchBarImp {
 	fn class_init(klass: &mut Self::Class) {
 		// The layout manager determines how child widgets are laid out.
 		klass.set_layout_manager_type::<gtk4::BinLayout>();
+		klass.set_accessible_role(AccessibleRole::Search);
 	}
 }
 
@@ -67,21 +105,20 @@ impl ObjectImpl for SearchBarImp {
 			.icon_name("folder-saved-search-symbolic")
 			.css_classes(vec!["search-button".into()])
 			.build();
+		a11y::set_label(&search_button, &tr("Search"));
 		holder.append(&search_button);
 
 		let entry = Entry::builder()
-			.placeholder_text("Find a setting...")
+			.placeholder_text(&tr("Find a setting..."))
 			.css_classes(vec!["search-bar-entry".into()])
 			.build();
+		a11y::set_label(&entry, &tr("Search settings"));
 		entry.hide();
-		search_button.connect_clicked(clone!(@weak holder, @weak entry => move |_| {
+		search_button.connect_clicked(clone!(@weak obj, @weak entry => move |_| {
 			if entry.get_visible() {
-				holder.set_css_classes(&["search-bar-inactive"]);
-				entry.hide();
-			}  else {
-				holder.set_css_classes(&["search-bar"]);
-				entry.show();
-				entry.grab_focus();
+				obj.close();
+			} else {
+				obj.open();
 			}
 		}));
 		holder.append(&entry);
@@ -49,6 +49,7 @@ impl ObjectSubclass for ListBoxSelectionRowImp {
 	fn class_init(klass: &mut Self::Class) {
 		// The layout manager determines how child widgets are laid out.
 		klass.set_layout_manager_type::<gtk4::BinLayout>();
+		klass.set_accessible_role(gtk4::AccessibleRole::ListItem);
 	}
 }
 